@@ -0,0 +1,65 @@
+// Scans generated programs of doubling size. The time per byte should stay
+// roughly the same as the input grows; the benchmark fails if it grows more
+// than `MAX_GROWTH` times from the smallest to the largest input, which is
+// 32 times larger, so a quadratic scanner would grow about 32 times.
+//
+//     cargo bench --bench scanner
+
+use std::time::{Duration, Instant};
+
+use rust_lox::scanner::Scanner;
+
+const CHUNK: &str = r#"// a comment with ünïcödé
+class Point {
+    init(x, y) { this.x = x; this.y = y; }
+    norm() { return this.x * this.x + this.y * this.y; }
+}
+var p = Point(3, 4.5);
+if (p.norm() >= 10 and p.x != nil) { print "ready → " + "set"; }
+"#;
+
+const MAX_GROWTH: f64 = 4.0;
+
+fn main() {
+    let mut first: Option<f64> = None;
+    let mut previous: Option<f64> = None;
+    for shift in 0..6 {
+        let repeat = 500 << shift;
+        let source = CHUNK.repeat(repeat);
+        let elapsed = time(&source);
+        let per_byte = elapsed.as_nanos() as f64 / source.len() as f64;
+        let ratio = previous.map_or(String::new(), |p| format!("  x{:.2}", per_byte / p));
+        println!(
+            "{:>9} bytes  {:>10.2?}  {:>6.2} ns/byte{}",
+            source.len(),
+            elapsed,
+            per_byte,
+            ratio
+        );
+        previous = Some(per_byte);
+        first = first.or(previous);
+    }
+
+    let growth = previous.unwrap() / first.unwrap();
+    println!("time per byte grew x{:.2} over a 32x larger input", growth);
+    assert!(
+        growth < MAX_GROWTH,
+        "scanning is not linear: time per byte grew x{:.2}",
+        growth
+    );
+}
+
+// The fastest of a few runs, to keep noise out of the comparison.
+fn time(source: &str) -> Duration {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            let (tokens, errors) = Scanner::new(source.to_string()).scan_tokens();
+            let elapsed = start.elapsed();
+            assert!(errors.is_empty());
+            assert!(tokens.len() > 1);
+            elapsed
+        })
+        .min()
+        .unwrap()
+}
@@ -0,0 +1,443 @@
+use std::cmp::Ordering;
+
+use crate::{
+    bigint::BigInt,
+    error::{Error, Result},
+    token::{Literal, Token},
+    token_type::TokenType,
+};
+
+// The numeric model: integers are `isize` and move to the `BigInt` tier when
+// a result doesn't fit, and back when it does again. Integer division by zero
+// is a runtime error. As soon as one operand is a float the other one is
+// converted and IEEE semantics apply, e.g. `1.0 / 0` is infinity.
+pub fn binary(operator: &Token, left: &Literal, right: &Literal) -> Result<Literal> {
+    match (left, right) {
+        (Literal::Isize(l), Literal::Isize(r)) => integer(operator, *l, *r),
+        (Literal::BigInt(l), Literal::BigInt(r)) => big_integer(operator, l, r),
+        (Literal::Isize(l), Literal::BigInt(r)) => big_integer(operator, &BigInt::from(*l), r),
+        (Literal::BigInt(l), Literal::Isize(r)) => big_integer(operator, l, &BigInt::from(*r)),
+        (Literal::Isize(l), Literal::Float(r)) => float(operator, *l as f64, *r),
+        (Literal::Float(l), Literal::Isize(r)) => float(operator, *l, *r as f64),
+        (Literal::BigInt(l), Literal::Float(r)) => float(operator, l.to_f64(), *r),
+        (Literal::Float(l), Literal::BigInt(r)) => float(operator, *l, r.to_f64()),
+        (Literal::Float(l), Literal::Float(r)) => float(operator, *l, *r),
+        (Literal::String(l), Literal::String(r)) if operator.token_type == TokenType::PLUS => {
+            Ok(Literal::String(format!("{}{}", l, r)))
+        }
+        _ if operator.token_type == TokenType::PLUS => Err(error(
+            operator,
+            "Operands must be two numbers or two strings.",
+        )),
+        _ => Err(error(operator, "Operands must be numbers.")),
+    }
+}
+
+// The order of two numbers or two strings; `None` for other values and NaN.
+pub fn compare_literals(left: &Literal, right: &Literal) -> Option<Ordering> {
+    match (left, right) {
+        (Literal::Isize(l), Literal::Isize(r)) => Some(l.cmp(r)),
+        (Literal::BigInt(l), Literal::BigInt(r)) => Some(l.cmp(r)),
+        (Literal::Isize(l), Literal::BigInt(r)) => Some(BigInt::from(*l).cmp(r)),
+        (Literal::BigInt(l), Literal::Isize(r)) => Some(l.cmp(&BigInt::from(*r))),
+        (Literal::String(l), Literal::String(r)) => Some(l.cmp(r)),
+        _ => to_float(left)?.partial_cmp(&to_float(right)?),
+    }
+}
+
+fn to_float(literal: &Literal) -> Option<f64> {
+    match literal {
+        Literal::Isize(i) => Some(*i as f64),
+        Literal::BigInt(i) => Some(i.to_f64()),
+        Literal::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+pub fn negate(operator: &Token, operand: &Literal) -> Result<Literal> {
+    match operand {
+        Literal::Isize(i) => Ok(match i.checked_neg() {
+            Some(i) => Literal::Isize(i),
+            None => normalize(-&BigInt::from(*i)),
+        }),
+        Literal::BigInt(i) => Ok(normalize(-i)),
+        Literal::Float(f) => Ok(Literal::Float(-f)),
+        _ => Err(error(operator, "Operand must be a number.")),
+    }
+}
+
+// `~x`, which is `-x - 1` for integers of any size.
+pub fn bitwise_not(operator: &Token, operand: &Literal) -> Result<Literal> {
+    match operand {
+        Literal::Isize(i) => Ok(Literal::Isize(!i)),
+        Literal::BigInt(i) => Ok(normalize(&-i - &BigInt::from(1))),
+        _ => Err(error(operator, "Operand of '~' must be an integer.")),
+    }
+}
+
+fn integer(operator: &Token, l: isize, r: isize) -> Result<Literal> {
+    let result = match operator.token_type {
+        TokenType::PLUS => l.checked_add(r),
+        TokenType::MINUS => l.checked_sub(r),
+        TokenType::STAR => l.checked_mul(r),
+        // `/` and `%` truncate toward zero, `~/` rounds toward negative infinity
+        TokenType::SLASH | TokenType::PERCENT | TokenType::TILDESLASH if r == 0 => {
+            return Err(error(operator, "Integer division by zero."))
+        }
+        TokenType::SLASH => l.checked_div(r),
+        TokenType::PERCENT => l.checked_rem(r),
+        TokenType::TILDESLASH => l.checked_div(r).map(|q| {
+            if l % r != 0 && (l < 0) != (r < 0) {
+                q - 1
+            } else {
+                q
+            }
+        }),
+        TokenType::STARSTAR if r < 0 => return Ok(Literal::Float((l as f64).powf(r as f64))),
+        TokenType::STARSTAR => u32::try_from(r).ok().and_then(|r| l.checked_pow(r)),
+        TokenType::AMPERSAND => Some(l & r),
+        TokenType::PIPE => Some(l | r),
+        TokenType::CARET => Some(l ^ r),
+        TokenType::LESSLESS | TokenType::GREATERGREATER if r < 0 => {
+            return Err(error(operator, "Shift amount must not be negative."))
+        }
+        TokenType::LESSLESS => u32::try_from(r)
+            .ok()
+            .and_then(|r| l.checked_shl(r))
+            .filter(|shifted| shifted >> r == l),
+        TokenType::GREATERGREATER => Some(l >> r.min(isize::BITS as isize - 1)),
+        _ => return compare(operator, l.cmp(&r)),
+    };
+    match result {
+        Some(i) => Ok(Literal::Isize(i)),
+        None => big_integer(operator, &BigInt::from(l), &BigInt::from(r)),
+    }
+}
+
+// Results of `**` and `<<` larger than this are errors, so a typo can't make
+// the interpreter allocate all memory.
+const MAX_BITS: u64 = 1 << 20;
+
+fn big_integer(operator: &Token, l: &BigInt, r: &BigInt) -> Result<Literal> {
+    let result = match operator.token_type {
+        TokenType::PLUS => l + r,
+        TokenType::MINUS => l - r,
+        TokenType::STAR => l * r,
+        TokenType::SLASH | TokenType::PERCENT | TokenType::TILDESLASH if r.is_zero() => {
+            return Err(error(operator, "Integer division by zero."))
+        }
+        TokenType::SLASH => l.div_rem(r).unwrap_or_default().0,
+        TokenType::PERCENT => l.div_rem(r).unwrap_or_default().1,
+        TokenType::TILDESLASH => floor_div(l, r),
+        // 0, 1 and -1 stay small however large the exponent is
+        TokenType::STARSTAR if l.bits() <= 1 && !r.is_negative() => {
+            BigInt::from(if l.is_zero() && !r.is_zero() {
+                0
+            } else if l.is_negative() && r.is_odd() {
+                -1
+            } else {
+                1
+            })
+        }
+        TokenType::STARSTAR => match r.to_isize() {
+            Some(r) if r < 0 => return Ok(Literal::Float(l.to_f64().powf(r as f64))),
+            Some(r) if l.bits().saturating_mul(r as u64) <= MAX_BITS => l.pow(r as u32),
+            _ => return Err(error(operator, "Result of '**' is too large.")),
+        },
+        TokenType::LESSLESS | TokenType::GREATERGREATER => {
+            let shift = match r.to_isize() {
+                Some(r) if r < 0 => {
+                    return Err(error(operator, "Shift amount must not be negative."))
+                }
+                Some(r) if (r as u64) <= MAX_BITS => r as u32,
+                _ if operator.token_type == TokenType::GREATERGREATER => {
+                    return Ok(Literal::Isize(if l.is_negative() { -1 } else { 0 }))
+                }
+                _ => return Err(error(operator, "Result of '<<' is too large.")),
+            };
+            let factor = BigInt::from(2).pow(shift);
+            if operator.token_type == TokenType::GREATERGREATER {
+                floor_div(l, &factor)
+            } else if l.bits() + shift as u64 <= MAX_BITS {
+                l * &factor
+            } else {
+                return Err(error(operator, "Result of '<<' is too large."));
+            }
+        }
+        TokenType::AMPERSAND | TokenType::PIPE | TokenType::CARET => {
+            return Err(error(
+                operator,
+                &format!(
+                    "Operands of '{}' must be integers between {} and {}.",
+                    operator.lexeme,
+                    isize::MIN,
+                    isize::MAX
+                ),
+            ))
+        }
+        _ => return compare(operator, l.cmp(r)),
+    };
+    Ok(normalize(result))
+}
+
+// Division rounding toward negative infinity; `r` must not be zero.
+fn floor_div(l: &BigInt, r: &BigInt) -> BigInt {
+    let (quotient, remainder) = l.div_rem(r).unwrap_or_default();
+    if !remainder.is_zero() && remainder.is_negative() != r.is_negative() {
+        &quotient - &BigInt::from(1)
+    } else {
+        quotient
+    }
+}
+
+// Results that fit in an `isize` go back to the fast tier.
+fn normalize(i: BigInt) -> Literal {
+    match i.to_isize() {
+        Some(i) => Literal::Isize(i),
+        None => Literal::BigInt(i),
+    }
+}
+
+fn float(operator: &Token, l: f64, r: f64) -> Result<Literal> {
+    let result = match operator.token_type {
+        TokenType::PLUS => l + r,
+        TokenType::MINUS => l - r,
+        TokenType::STAR => l * r,
+        TokenType::SLASH => l / r,
+        TokenType::PERCENT => l % r,
+        TokenType::TILDESLASH => (l / r).floor(),
+        TokenType::STARSTAR => l.powf(r),
+        TokenType::AMPERSAND
+        | TokenType::PIPE
+        | TokenType::CARET
+        | TokenType::LESSLESS
+        | TokenType::GREATERGREATER => {
+            return Err(error(
+                operator,
+                &format!("Operands of '{}' must be integers.", operator.lexeme),
+            ))
+        }
+        // every comparison with NaN is false
+        _ => match l.partial_cmp(&r) {
+            Some(ordering) => return compare(operator, ordering),
+            None => return Ok(Literal::Bool(false)),
+        },
+    };
+    Ok(Literal::Float(result))
+}
+
+fn compare(operator: &Token, ordering: Ordering) -> Result<Literal> {
+    let result = match operator.token_type {
+        TokenType::GREATER => ordering.is_gt(),
+        TokenType::GREATEREQUAL => ordering.is_ge(),
+        TokenType::LESS => ordering.is_lt(),
+        TokenType::LESSEQUAL => ordering.is_le(),
+        _ => {
+            return Err(error(
+                operator,
+                &format!("Unsupported operator '{}'.", operator.lexeme),
+            ))
+        }
+    };
+    Ok(Literal::Bool(result))
+}
+
+fn error(operator: &Token, message: &str) -> Error {
+    Error::RuntimeError(Box::new(operator.clone()), String::from(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token::Span;
+
+    fn op(token_type: TokenType, lexeme: &str) -> Token {
+        Token::new(token_type, lexeme.into(), Literal::None, Span::default())
+    }
+
+    fn big(digits: &str) -> Literal {
+        let (negative, digits) = match digits.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, digits),
+        };
+        let i = BigInt::from_str_radix(digits, 10).unwrap();
+        Literal::BigInt(if negative { -&i } else { i })
+    }
+
+    fn pow(l: Literal, r: Literal) -> Result<Literal> {
+        binary(&op(TokenType::STARSTAR, "**"), &l, &r)
+    }
+
+    fn eval(token_type: TokenType, lexeme: &str, l: Literal, r: Literal) -> Result<Literal> {
+        binary(&op(token_type, lexeme), &l, &r)
+    }
+
+    fn message(result: Result<Literal>) -> String {
+        match result {
+            Err(Error::RuntimeError(_, message)) => message,
+            result => panic!("expected a runtime error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn pow_of_trivial_bases_ignores_the_size_of_the_exponent() {
+        let huge = Literal::Isize(5_000_000_000);
+        assert_eq!(
+            pow(Literal::Isize(1), huge.clone()).unwrap(),
+            Literal::Isize(1)
+        );
+        assert_eq!(
+            pow(Literal::Isize(0), huge.clone()).unwrap(),
+            Literal::Isize(0)
+        );
+        assert_eq!(pow(Literal::Isize(-1), huge).unwrap(), Literal::Isize(1));
+        assert_eq!(
+            pow(Literal::Isize(-1), Literal::Isize(5_000_000_001)).unwrap(),
+            Literal::Isize(-1)
+        );
+        assert_eq!(
+            pow(Literal::Isize(-1), big("100000000000000000000001")).unwrap(),
+            Literal::Isize(-1)
+        );
+        assert_eq!(
+            pow(Literal::Isize(0), Literal::Isize(0)).unwrap(),
+            Literal::Isize(1)
+        );
+    }
+
+    #[test]
+    fn pow_still_rejects_huge_results() {
+        assert!(pow(Literal::Isize(2), Literal::Isize(5_000_000_000)).is_err());
+        assert_eq!(
+            pow(Literal::Isize(2), Literal::Isize(64)).unwrap(),
+            big("18446744073709551616")
+        );
+        assert_eq!(
+            pow(Literal::Isize(2), Literal::Isize(-1)).unwrap(),
+            Literal::Float(0.5)
+        );
+    }
+
+    #[test]
+    fn overflow_promotes_instead_of_wrapping() {
+        let max = Literal::Isize(isize::MAX);
+        assert_eq!(
+            eval(TokenType::PLUS, "+", max.clone(), Literal::Isize(1)).unwrap(),
+            big("9223372036854775808")
+        );
+        assert_eq!(
+            eval(TokenType::STAR, "*", max.clone(), Literal::Isize(2)).unwrap(),
+            big("18446744073709551614")
+        );
+        assert_eq!(
+            eval(
+                TokenType::MINUS,
+                "-",
+                Literal::Isize(isize::MIN),
+                Literal::Isize(1)
+            )
+            .unwrap(),
+            big("-9223372036854775809")
+        );
+        assert_eq!(
+            negate(&op(TokenType::MINUS, "-"), &Literal::Isize(isize::MIN)).unwrap(),
+            big("9223372036854775808")
+        );
+        assert_eq!(
+            eval(
+                TokenType::SLASH,
+                "/",
+                Literal::Isize(isize::MIN),
+                Literal::Isize(-1)
+            )
+            .unwrap(),
+            big("9223372036854775808")
+        );
+    }
+
+    #[test]
+    fn integer_division_by_zero_is_an_error() {
+        for (token_type, lexeme) in [
+            (TokenType::SLASH, "/"),
+            (TokenType::PERCENT, "%"),
+            (TokenType::TILDESLASH, "~/"),
+        ] {
+            assert_eq!(
+                message(eval(
+                    token_type,
+                    lexeme,
+                    Literal::Isize(1),
+                    Literal::Isize(0)
+                )),
+                "Integer division by zero."
+            );
+            assert_eq!(
+                message(eval(
+                    token_type,
+                    lexeme,
+                    big("100000000000000000000"),
+                    Literal::Isize(0)
+                )),
+                "Integer division by zero."
+            );
+        }
+    }
+
+    #[test]
+    fn floats_follow_ieee() {
+        assert_eq!(
+            eval(
+                TokenType::SLASH,
+                "/",
+                Literal::Float(1.0),
+                Literal::Isize(0)
+            )
+            .unwrap(),
+            Literal::Float(f64::INFINITY)
+        );
+        assert_eq!(
+            eval(TokenType::PLUS, "+", Literal::Isize(1), Literal::Float(0.5)).unwrap(),
+            Literal::Float(1.5)
+        );
+        assert_eq!(
+            eval(
+                TokenType::LESS,
+                "<",
+                Literal::Float(f64::NAN),
+                Literal::Isize(1)
+            )
+            .unwrap(),
+            Literal::Bool(false)
+        );
+    }
+
+    #[test]
+    fn integer_division_truncates() {
+        assert_eq!(
+            eval(TokenType::SLASH, "/", Literal::Isize(7), Literal::Isize(2)).unwrap(),
+            Literal::Isize(3)
+        );
+        assert_eq!(
+            eval(TokenType::SLASH, "/", Literal::Isize(-7), Literal::Isize(2)).unwrap(),
+            Literal::Isize(-3)
+        );
+    }
+
+    #[test]
+    fn operands_must_be_numbers() {
+        let string = Literal::String(String::from("a"));
+        assert_eq!(
+            message(eval(
+                TokenType::PLUS,
+                "+",
+                string.clone(),
+                Literal::Isize(1)
+            )),
+            "Operands must be two numbers or two strings."
+        );
+        assert_eq!(
+            message(eval(TokenType::STAR, "*", string, Literal::Isize(1))),
+            "Operands must be numbers."
+        );
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     expr::{Acceptor as ExprAcceptor, Expr, Visitor as ExprVisitor},
     stmt::{Acceptor as StmtAcceptor, Stmt, Visitor as StmtVisitor},
     token::{Literal, Token},
+    token_type::TokenType,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -16,21 +17,65 @@ impl AstPrinter {
         let mut string = String::new();
         for statement in statements {
             string.push_str(&statement.accept(self));
-            string.push_str("\n");
+            string.push('\n');
         }
         string
     }
 
     fn parenthesize(&mut self, name: String, exprs: Vec<Expr>) -> String {
-        let mut string = String::new();
-        string.push_str("(");
-        string.push_str(&name);
-        for expr in exprs {
-            string.push_str(" ");
-            string.push_str(&expr.accept(self))
+        let parts = exprs.iter().map(|expr| expr.accept(self)).collect();
+        group(&name, parts)
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) -> Vec<String> {
+        statements
+            .iter()
+            .map(|statement| statement.accept(self))
+            .collect()
+    }
+}
+
+// `(name part...)` for parts that are already printed.
+fn group(name: &str, parts: Vec<String>) -> String {
+    let mut string = String::new();
+    string.push('(');
+    string.push_str(name);
+    for part in parts {
+        string.push(' ');
+        string.push_str(&part)
+    }
+    string.push(')');
+    string
+}
+
+// `(a b)` for the parameters `a, b`.
+fn parameters(params: &[Token]) -> String {
+    let names: Vec<&str> = params.iter().map(|param| param.lexeme.as_str()).collect();
+    format!("({})", names.join(" "))
+}
+
+// A loop as `outer: (while ...)` when it has a label.
+fn labeled(label: &Option<Token>, string: String) -> String {
+    match label {
+        Some(label) => format!("{}: {}", label.lexeme, string),
+        None => string,
+    }
+}
+
+// `(break)` or `(continue outer)`.
+fn jump(keyword: &Token, label: &Option<Token>) -> String {
+    let parts = label.iter().map(|label| label.lexeme.clone()).collect();
+    group(&keyword.lexeme, parts)
+}
+
+// `x += 1` as `(+= x 1)`, `++x` as `(++ x)` and `x++` as `(post++ x)`.
+fn compound(operator: &Token, target: String, value: String, postfix: bool) -> String {
+    match operator.token_type {
+        TokenType::PLUSPLUS | TokenType::MINUSMINUS if postfix => {
+            group(&format!("post{}", operator.lexeme), vec![target])
         }
-        string.push_str(")");
-        string
+        TokenType::PLUSPLUS | TokenType::MINUSMINUS => group(&operator.lexeme, vec![target]),
+        _ => group(&operator.lexeme, vec![target, value]),
     }
 }
 
@@ -45,6 +90,7 @@ impl ExprVisitor<String> for AstPrinter {
         match expr {
             Literal::None => String::from("nil"),
             Literal::Isize(u) => u.to_string(),
+            Literal::BigInt(i) => i.to_string(),
             Literal::Float(f) => f.to_string(),
             Literal::String(s) => String::from(s),
             Literal::Bool(b) => b.to_string(),
@@ -55,35 +101,128 @@ impl ExprVisitor<String> for AstPrinter {
     }
 
     fn visit_variable(&mut self, name: &Token) -> String {
-        todo!()
+        name.lexeme.clone()
     }
 
     fn visit_assign(&mut self, name: &Token, value: &Expr) -> String {
-        todo!()
+        group("=", vec![name.lexeme.clone(), value.accept(self)])
     }
 
     fn visit_logical(&mut self, left: &Expr, operator: &Token, right: &Expr) -> String {
-        todo!()
+        self.parenthesize(operator.lexeme.clone(), vec![left.clone(), right.clone()])
     }
 
-    fn visit_call(&mut self, callee: &Expr, paren: &Token, arguments: &[Expr]) -> String {
-        todo!()
+    fn visit_call(&mut self, callee: &Expr, _paren: &Token, arguments: &[Expr]) -> String {
+        let mut parts = vec![callee.accept(self)];
+        parts.extend(arguments.iter().map(|argument| argument.accept(self)));
+        group("call", parts)
     }
 
     fn visit_get(&mut self, object: &Expr, name: &Token) -> String {
-        todo!()
+        group(".", vec![object.accept(self), name.lexeme.clone()])
     }
 
     fn visit_set(&mut self, object: &Expr, name: &Token, value: &Expr) -> String {
-        todo!()
+        let target = self.visit_get(object, name);
+        group("=", vec![target, value.accept(self)])
+    }
+
+    fn visit_compound_assign(
+        &mut self,
+        name: &Token,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> String {
+        compound(operator, name.lexeme.clone(), value.accept(self), postfix)
+    }
+
+    fn visit_compound_set(
+        &mut self,
+        object: &Expr,
+        name: &Token,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> String {
+        let target = self.visit_get(object, name);
+        compound(operator, target, value.accept(self), postfix)
+    }
+
+    fn visit_this(&mut self, _keyword: &Token) -> String {
+        String::from("this")
+    }
+
+    fn visit_super(&mut self, _keyword: &Token, method: &Token) -> String {
+        group("super", vec![method.lexeme.clone()])
+    }
+
+    fn visit_interpolation(&mut self, parts: &[Expr]) -> String {
+        self.parenthesize(String::from("interpolate"), parts.to_vec())
+    }
+
+    fn visit_lambda(&mut self, _keyword: &Token, params: &[Token], body: &[Stmt]) -> String {
+        let mut parts = vec![parameters(params)];
+        parts.extend(self.statements(body));
+        group("fun", parts)
+    }
+
+    fn visit_list(&mut self, elements: &[Expr]) -> String {
+        self.parenthesize(String::from("list"), elements.to_vec())
+    }
+
+    fn visit_map(&mut self, entries: &[(Expr, Expr)], _brace: &Token) -> String {
+        let parts = entries
+            .iter()
+            .flat_map(|(key, value)| [key.clone(), value.clone()])
+            .collect();
+        self.parenthesize(String::from("map"), parts)
     }
 
-    fn visit_this(&mut self, keyword: &Token) -> String {
-        todo!()
+    fn visit_index(&mut self, object: &Expr, _bracket: &Token, index: &Expr) -> String {
+        self.parenthesize(String::from("index"), vec![object.clone(), index.clone()])
     }
 
-    fn visit_super(&mut self, keyword: &Token, method: &Token) -> String {
-        todo!()
+    fn visit_set_index(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> String {
+        let target = self.visit_index(object, bracket, index);
+        group("=", vec![target, value.accept(self)])
+    }
+
+    // A missing bound is printed as `_`.
+    fn visit_slice(
+        &mut self,
+        object: &Expr,
+        _bracket: &Token,
+        start: &Option<Box<Expr>>,
+        end: &Option<Box<Expr>>,
+    ) -> String {
+        let mut parts = vec![object.accept(self)];
+        for bound in [start, end] {
+            parts.push(match bound {
+                Some(bound) => bound.accept(self),
+                None => String::from("_"),
+            });
+        }
+        group("slice", parts)
+    }
+
+    fn visit_compound_set_index(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> String {
+        let target = self.visit_index(object, bracket, index);
+        compound(operator, target, value.accept(self), postfix)
     }
 }
 
@@ -95,16 +234,17 @@ impl StmtVisitor<String> for AstPrinter {
         let mut string = String::new();
         string.push_str("(print ");
         string.push_str(&expression.accept(self));
-        string.push_str(")");
+        string.push(')');
         string
     }
 
     fn visit_var_stmt(&mut self, name: &Token, initializer: &Expr) -> String {
-        todo!()
+        group("var", vec![name.lexeme.clone(), initializer.accept(self)])
     }
 
     fn visit_block_stmt(&mut self, statements: &[Stmt]) -> String {
-        todo!()
+        let parts = self.statements(statements);
+        group("block", parts)
     }
 
     fn visit_if_stmt(
@@ -113,19 +253,55 @@ impl StmtVisitor<String> for AstPrinter {
         then_branch: &Stmt,
         else_branch: &Option<Box<Stmt>>,
     ) -> String {
-        todo!()
+        let mut parts = vec![condition.accept(self), then_branch.accept(self)];
+        parts.extend(else_branch.as_ref().map(|branch| branch.accept(self)));
+        group("if", parts)
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: &Option<Box<Expr>>,
+        label: &Option<Token>,
+    ) -> String {
+        let mut parts = vec![condition.accept(self), body.accept(self)];
+        parts.extend(increment.as_ref().map(|increment| increment.accept(self)));
+        labeled(label, group("while", parts))
+    }
+
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &Token,
+        _keyword: &Token,
+        iterable: &Expr,
+        body: &Stmt,
+        label: &Option<Token>,
+    ) -> String {
+        let parts = vec![
+            name.lexeme.clone(),
+            iterable.accept(self),
+            body.accept(self),
+        ];
+        labeled(label, group("for-in", parts))
+    }
+
+    fn visit_break_stmt(&mut self, keyword: &Token, label: &Option<Token>) -> String {
+        jump(keyword, label)
     }
 
-    fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> String {
-        todo!()
+    fn visit_continue_stmt(&mut self, keyword: &Token, label: &Option<Token>) -> String {
+        jump(keyword, label)
     }
 
     fn visit_function_stmt(&mut self, name: &Token, params: &[Token], body: &[Stmt]) -> String {
-        todo!()
+        let mut parts = vec![name.lexeme.clone(), parameters(params)];
+        parts.extend(self.statements(body));
+        group("fun", parts)
     }
 
-    fn visit_return_stmt(&mut self, keyword: &Token, value: &Expr) -> String {
-        todo!()
+    fn visit_return_stmt(&mut self, _keyword: &Token, value: &Expr) -> String {
+        group("return", vec![value.accept(self)])
     }
 
     fn visit_class_stmt(
@@ -134,6 +310,96 @@ impl StmtVisitor<String> for AstPrinter {
         super_class: &Option<Expr>,
         methods: &[Stmt],
     ) -> String {
-        todo!()
+        let mut parts = vec![name.lexeme.clone()];
+        if let Some(super_class) = super_class {
+            parts.push(group("<", vec![super_class.accept(self)]));
+        }
+        parts.extend(self.statements(methods));
+        group("class", parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::Parser, scanner::Scanner};
+
+    fn print(source: &str) -> String {
+        let (tokens, errors) = Scanner::new(source.to_string()).scan_tokens();
+        assert!(errors.is_empty());
+        let (statements, errors) = Parser::new(tokens).parse();
+        assert!(errors.is_empty());
+        AstPrinter {}.print_statements(statements)
+    }
+
+    #[test]
+    fn prints_statements_and_expressions() {
+        assert_eq!(print("print -1 + 2 * 3;"), "(print (+ (- 1) (* 2 3)))\n");
+        assert_eq!(print("var a = b or c;"), "(var a (or b c))\n");
+        assert_eq!(
+            print("a.b = f(1, c.d);"),
+            "(= (. a b) (call f 1 (. c d)))\n"
+        );
+        assert_eq!(
+            print("if (a) { x = 1; } else print 2;"),
+            "(if a (block (= x 1)) (print 2))\n"
+        );
+        assert_eq!(
+            print("class B < A { m(x) { return super.m(x) + this.y; } }"),
+            "(class B (< A) (fun m (x) (return (+ (call (super m) x) (. this y)))))\n"
+        );
+    }
+
+    #[test]
+    fn prints_compound_assignments() {
+        assert_eq!(print("x += 2;"), "(+= x 2)\n");
+        assert_eq!(print("++x;"), "(++ x)\n");
+        assert_eq!(print("x--;"), "(post-- x)\n");
+        assert_eq!(print("a.b *= c;"), "(*= (. a b) c)\n");
+        assert_eq!(print("a.b++;"), "(post++ (. a b))\n");
+    }
+
+    #[test]
+    fn prints_lambdas() {
+        assert_eq!(
+            print("var f = fun (a, b) { return a; };"),
+            "(var f (fun (a b) (return a)))\n"
+        );
+        assert_eq!(print("g(() => 1);"), "(call g (fun () (return 1)))\n");
+    }
+
+    #[test]
+    fn prints_lists_indexing_and_slices() {
+        assert_eq!(print("print [1, [2]];"), "(print (list 1 (list 2)))\n");
+        assert_eq!(
+            print("xs[0] = xs[-1];"),
+            "(= (index xs 0) (index xs (- 1)))\n"
+        );
+        assert_eq!(print("xs[i] += 1;"), "(+= (index xs i) 1)\n");
+        assert_eq!(print("xs[i]++;"), "(post++ (index xs i))\n");
+        assert_eq!(print("print xs[1:];"), "(print (slice xs 1 _))\n");
+        assert_eq!(print("print xs[:n];"), "(print (slice xs _ n))\n");
+    }
+
+    #[test]
+    fn prints_maps() {
+        assert_eq!(print("print {1: 2, k: v};"), "(print (map 1 2 k v))\n");
+        assert_eq!(print("print {};"), "(print (map))\n");
+    }
+
+    #[test]
+    fn prints_loops_and_jumps() {
+        assert_eq!(
+            print("while (a) { break; continue; }"),
+            "(while a (block (break) (continue)))\n"
+        );
+        assert_eq!(
+            print("outer: for (var i = 0; i < 2; i++) continue outer;"),
+            "(block (var i 0) outer: (while (< i 2) (continue outer) (post++ i)))\n"
+        );
+        assert_eq!(
+            print("outer: for (var k in {1: 2}) print k;"),
+            "outer: (for-in k (map 1 2) (print k))\n"
+        );
     }
 }
@@ -0,0 +1,425 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+// An arbitrary-precision integer, used for values that don't fit in an
+// `isize`. The magnitude is stored in base 2^32, least significant limb first,
+// without trailing zero limbs, so every value has exactly one representation
+// and the derived `Eq` and `Hash` are correct.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        BigInt {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    // Parses digits in `radix` without sign or prefix; `None` if a digit is
+    // invalid or there are none.
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<BigInt> {
+        if digits.is_empty() {
+            return None;
+        }
+        let mut magnitude = vec![];
+        for c in digits.chars() {
+            let digit = c.to_digit(radix)?;
+            mul_add_small(&mut magnitude, radix, digit);
+        }
+        Some(BigInt::new(false, magnitude))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.magnitude.first().is_some_and(|low| low & 1 == 1)
+    }
+
+    // The number of bits of the magnitude.
+    pub fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(top) => self.magnitude.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    pub fn to_isize(&self) -> Option<isize> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let value = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0i128, |value, &limb| value << 32 | limb as i128);
+        let value = if self.negative { -value } else { value };
+        isize::try_from(value).ok()
+    }
+
+    // The integer equal to `f`; `None` if it is not finite or has a fraction.
+    pub fn from_f64(f: f64) -> Option<BigInt> {
+        if !f.is_finite() || f.fract() != 0.0 {
+            return None;
+        }
+        // f == mantissa * 2^exponent
+        let bits = f.to_bits();
+        let biased = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = match biased {
+            0 => (fraction, -1074),
+            _ => (fraction | 1 << 52, biased - 1075),
+        };
+        if exponent < 0 {
+            let value = mantissa.checked_shr(-exponent as u32).unwrap_or(0);
+            return Some(BigInt::new(
+                f < 0.0,
+                vec![value as u32, (value >> 32) as u32],
+            ));
+        }
+        let mut magnitude = vec![0; exponent as usize / 32];
+        magnitude.extend([mantissa as u32, (mantissa >> 32) as u32]);
+        mul_add_small(&mut magnitude, 1 << (exponent % 32), 0);
+        Some(BigInt::new(f < 0.0, magnitude))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let value = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0.0, |value, &limb| value * 4294967296.0 + limb as f64);
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    // Division truncating toward zero, like `isize`'s; `None` when dividing
+    // by zero.
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+        Some((
+            BigInt::new(self.negative != divisor.negative, quotient),
+            BigInt::new(self.negative, remainder),
+        ))
+    }
+}
+
+impl From<isize> for BigInt {
+    fn from(i: isize) -> BigInt {
+        let value = i.unsigned_abs() as u64;
+        BigInt::new(i < 0, vec![value as u32, (value >> 32) as u32])
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            );
+        }
+        match compare_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::new(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::new(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.magnitude.iter().enumerate() {
+                let t = product[i + j] as u64 + a as u64 * b as u64 + carry;
+                product[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            product[i + other.magnitude.len()] = carry as u32;
+        }
+        BigInt::new(self.negative != other.negative, product)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // nine decimal digits at a time, least significant first
+        let mut chunks = vec![];
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            chunks.push(div_rem_small(&mut magnitude, 1_000_000_000));
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let t = limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(t as u32);
+        carry = t >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+// `a - b` where `a >= b`.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut t = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if t < 0 {
+            t += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(t as u32);
+    }
+    difference
+}
+
+fn mul_add_small(magnitude: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in magnitude.iter_mut() {
+        let t = *limb as u64 * factor as u64 + carry;
+        *limb = t as u32;
+        carry = t >> 32;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+// Divides in place and returns the remainder.
+fn div_rem_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for limb in magnitude.iter_mut().rev() {
+        let t = remainder << 32 | *limb as u64;
+        *limb = (t / divisor as u64) as u32;
+        remainder = t % divisor as u64;
+    }
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    remainder as u32
+}
+
+// Long division one bit at a time, which is plenty for numbers that are a
+// few limbs long.
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = vec![];
+    for i in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + bit i of a
+        let bit = a[i / 32] >> (i % 32) & 1;
+        mul_add_small(&mut remainder, 2, bit);
+        if compare_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[i / 32] |= 1 << (i % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(digits: &str) -> BigInt {
+        match digits.strip_prefix('-') {
+            Some(digits) => -&BigInt::from_str_radix(digits, 10).unwrap(),
+            None => BigInt::from_str_radix(digits, 10).unwrap(),
+        }
+    }
+
+    #[test]
+    fn isize_bounds_round_trip() {
+        for i in [isize::MIN, isize::MIN + 1, -1, 0, 1, isize::MAX] {
+            assert_eq!(BigInt::from(i).to_isize(), Some(i));
+            assert_eq!(BigInt::from(i).to_string(), i.to_string());
+        }
+    }
+
+    #[test]
+    fn whole_floats_convert_exactly() {
+        assert_eq!(BigInt::from_f64(1e20), Some(big("100000000000000000000")));
+        assert_eq!(
+            BigInt::from_f64(-18446744073709551616.0),
+            Some(big("-18446744073709551616"))
+        );
+        assert_eq!(BigInt::from_f64(-3.0), Some(BigInt::from(-3)));
+        assert_eq!(BigInt::from_f64(0.0), Some(BigInt::from(0)));
+        assert_eq!(BigInt::from_f64(-0.0), Some(BigInt::from(0)));
+        assert_eq!(BigInt::from_f64(0.5), None);
+        assert_eq!(BigInt::from_f64(f64::INFINITY), None);
+        assert_eq!(BigInt::from_f64(f64::NAN), None);
+    }
+
+    #[test]
+    fn values_just_outside_isize_do_not_demote() {
+        let max = BigInt::from(isize::MAX);
+        let min = BigInt::from(isize::MIN);
+        let one = BigInt::from(1);
+        assert_eq!((&max + &one).to_isize(), None);
+        assert_eq!((&min - &one).to_isize(), None);
+        assert_eq!((-&min).to_isize(), None);
+        assert_eq!((-&min).to_string(), "9223372036854775808");
+        assert_eq!((&(&max + &one) - &one).to_isize(), Some(isize::MAX));
+        assert_eq!((&(&min - &one) + &one).to_isize(), Some(isize::MIN));
+    }
+
+    #[test]
+    fn division_truncates_toward_zero_and_keeps_the_dividend_sign() {
+        let cases = [
+            ("100000000000000000007", "10", "10000000000000000000", "7"),
+            (
+                "-100000000000000000007",
+                "10",
+                "-10000000000000000000",
+                "-7",
+            ),
+            ("100000000000000000007", "-10", "-10000000000000000000", "7"),
+            (
+                "-100000000000000000007",
+                "-10",
+                "10000000000000000000",
+                "-7",
+            ),
+        ];
+        for (l, r, quotient, remainder) in cases {
+            let (q, rem) = big(l).div_rem(&big(r)).unwrap();
+            assert_eq!((q, rem), (big(quotient), big(remainder)), "{} / {}", l, r);
+        }
+        assert_eq!(big("5").div_rem(&BigInt::default()), None);
+        assert!(!big("-3").div_rem(&big("3")).unwrap().1.is_negative());
+    }
+
+    #[test]
+    fn values_with_several_limbs_display_in_decimal() {
+        for digits in [
+            "18446744073709551616",
+            "-340282366920938463463374607431768211457",
+            "1000000000000000000000000000000000000000000000000000000000000001",
+        ] {
+            assert_eq!(big(digits).to_string(), digits);
+        }
+        assert_eq!(
+            BigInt::from(2).pow(100).to_string(),
+            "1267650600228229401496703205376"
+        );
+        assert_eq!(BigInt::default().to_string(), "0");
+    }
+
+    #[test]
+    fn zero_has_one_representation() {
+        assert_eq!(-&BigInt::default(), BigInt::default());
+        assert_eq!(&big("-5") + &big("5"), BigInt::default());
+        assert!(!(&big("-5") + &big("5")).is_negative());
+    }
+}
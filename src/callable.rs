@@ -1,7 +1,6 @@
 use std::cell::RefCell;
 use std::fmt;
 use std::rc::Rc;
-use std::time::SystemTime;
 
 use crate::environment::Environment;
 use crate::error::{Error, Result};
@@ -12,7 +11,31 @@ use crate::{interpreter::Interpreter, object::Object};
 
 pub trait LoxCallable {
     fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object>;
-    fn arity(&self) -> usize;
+    fn arity(&self) -> Arity;
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Arity {
+    Fixed(usize),
+    Variadic,
+}
+
+impl Arity {
+    pub fn accepts(&self, count: usize) -> bool {
+        match self {
+            Arity::Fixed(n) => *n == count,
+            Arity::Variadic => true,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arity::Fixed(n) => write!(f, "{}", n),
+            Arity::Variadic => write!(f, "any number of"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -70,13 +93,13 @@ impl LoxCallable for LoxFunction {
                 }
                 Ok(Object::Literal(Literal::None))
             }
-            Err(Error::Return(return_value)) => Ok(return_value),
+            Err(Error::Return(return_value)) => Ok(*return_value),
             Err(e) => Err(e),
         }
     }
 
-    fn arity(&self) -> usize {
-        self.params.len()
+    fn arity(&self) -> Arity {
+        Arity::Fixed(self.params.len())
     }
 }
 
@@ -86,24 +109,52 @@ impl fmt::Display for LoxFunction {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Clock {}
+pub type NativeFn = dyn Fn(&mut Interpreter, Vec<Object>) -> Result<Object>;
+
+// A function implemented by the host application. Returning
+// `Error::NativeError` reports a runtime error at the call site.
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    arity: Arity,
+    function: Rc<NativeFn>,
+}
 
-impl LoxCallable for Clock {
-    fn call(&self, _interpreter: &mut Interpreter, _arguments: Vec<Object>) -> Result<Object> {
-        match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => Ok(Object::Literal(Literal::Isize(n.as_millis() as isize))),
-            Err(_) => panic!("SystemTime before UNIX EPOCH!"),
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: Arity, function: F) -> NativeFunction
+    where
+        F: Fn(&mut Interpreter, Vec<Object>) -> Result<Object> + 'static,
+    {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            function: Rc::new(function),
         }
     }
-    fn arity(&self) -> usize {
-        0
+}
+
+impl LoxCallable for NativeFunction {
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object> {
+        (self.function)(interpreter, arguments)
+    }
+
+    fn arity(&self) -> Arity {
+        self.arity
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
     }
 }
 
-impl fmt::Display for Clock {
+impl fmt::Display for NativeFunction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "<native fn>")
+        write!(f, "<native fn {}>", self.name)
     }
 }
 
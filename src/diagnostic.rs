@@ -0,0 +1,474 @@
+use std::fmt::{self, Write};
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    error::Error,
+    token::{FileId, Span},
+};
+
+// The source text of every program run in a session, so errors can be shown
+// with the lines they point at.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub name: String,
+    pub text: String,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap::default()
+    }
+
+    pub fn add(&mut self, name: &str, text: &str) -> FileId {
+        self.files.push(SourceFile {
+            name: name.to_string(),
+            text: text.to_string(),
+        });
+        self.files.len() - 1
+    }
+
+    pub fn get(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file)
+    }
+
+    // The text of line `line` (starting at 1) without its line terminator.
+    pub fn line(&self, file: FileId, line: usize) -> Option<&str> {
+        let text = &self.get(file)?.text;
+        let line = text.split('\n').nth(line.checked_sub(1)?)?;
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+// The phase of the interpreter that found the problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Scan,
+    Parse,
+    Resolve,
+    Runtime,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase::Scan => write!(f, "scan"),
+            Phase::Parse => write!(f, "parse"),
+            Phase::Resolve => write!(f, "resolve"),
+            Phase::Runtime => write!(f, "runtime"),
+        }
+    }
+}
+
+// Extra information attached to an error, e.g. where a variable was declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl Note {
+    pub fn new(message: &str, span: Option<Span>) -> Note {
+        Note {
+            message: message.to_string(),
+            span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub phase: Phase,
+    pub message: String,
+    pub span: Option<Span>,
+    pub notes: Vec<Note>,
+}
+
+impl Diagnostic {
+    pub fn from_error(error: &Error) -> Diagnostic {
+        let (phase, message, notes) = match error {
+            Error::ScanError(_, message) => (Phase::Scan, message.clone(), vec![]),
+            Error::ParseError(_, message) => (Phase::Parse, message.clone(), vec![]),
+            Error::ResolveError(_, message) => (Phase::Resolve, message.clone(), vec![]),
+            Error::RuntimeError(_, message) | Error::NativeError(message) => {
+                (Phase::Runtime, message.clone(), vec![])
+            }
+            Error::Return(_) | Error::Break(_) | Error::Continue(_) => {
+                (Phase::Runtime, error.to_string(), vec![])
+            }
+            Error::WithNotes(error, notes) => {
+                let mut diagnostic = Diagnostic::from_error(error);
+                diagnostic.notes.extend(notes.iter().cloned());
+                return diagnostic;
+            }
+        };
+        Diagnostic {
+            severity: Severity::Error,
+            phase,
+            message,
+            span: error.span(),
+            notes,
+        }
+    }
+
+    // Renders the diagnostic the way rustc does: a header, the source line
+    // with the span underlined, and the notes below it.
+    pub fn render(&self, sources: &SourceMap, color: bool) -> String {
+        let style = Style { color };
+        let gutter = self
+            .notes
+            .iter()
+            .filter_map(|note| note.span)
+            .chain(self.span)
+            .map(|span| span.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{}{}",
+            style.paint(
+                severity_color(self.severity),
+                &format!("{}[{}]", self.severity, self.phase)
+            ),
+            style.bold(&format!(": {}", self.message))
+        );
+        if let Some(span) = self.span {
+            snippet(
+                &mut out,
+                sources,
+                span,
+                gutter,
+                severity_color(self.severity),
+                style,
+            );
+        }
+
+        for note in &self.notes {
+            match note.span {
+                Some(span) => {
+                    let _ = writeln!(
+                        out,
+                        "{}{}",
+                        style.paint(NOTE, "note"),
+                        style.bold(&format!(": {}", note.message))
+                    );
+                    snippet(&mut out, sources, span, gutter, NOTE, style);
+                }
+                None => {
+                    let _ = writeln!(
+                        out,
+                        "{:gutter$} {} note: {}",
+                        "",
+                        style.paint(GUTTER, "="),
+                        note.message
+                    );
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Diagnostic {
+    // A single-line JSON object for tools, e.g.
+    // `{"severity":"error","phase":"parse","message":"...","file":"a.lox",...}`.
+    pub fn to_json(&self, sources: &SourceMap) -> String {
+        let notes: Vec<String> = self
+            .notes
+            .iter()
+            .map(|note| {
+                format!(
+                    "{{\"message\":{},{}}}",
+                    json_string(&note.message),
+                    json_location(note.span, sources)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"severity\":\"{}\",\"phase\":\"{}\",\"message\":{},{},\"notes\":[{}]}}",
+            self.severity,
+            self.phase,
+            json_string(&self.message),
+            json_location(self.span, sources),
+            notes.join(",")
+        )
+    }
+}
+
+// The `file`, `line`, `column` and `span` members, `null` where unknown.
+fn json_location(span: Option<Span>, sources: &SourceMap) -> String {
+    let span = match span {
+        Some(span) => span,
+        None => return String::from("\"file\":null,\"line\":null,\"column\":null,\"span\":null"),
+    };
+    let file = match span.file.and_then(|file| sources.get(file)) {
+        Some(file) => json_string(&file.name),
+        None => String::from("null"),
+    };
+    format!(
+        "\"file\":{},\"line\":{},\"column\":{},\"span\":{{\"start\":{},\"end\":{},\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{}}}",
+        file,
+        span.line,
+        span.column,
+        span.start,
+        span.end,
+        span.line,
+        span.column,
+        span.end_line,
+        span.end_column
+    )
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// ANSI colors, only emitted when rendering with `color`.
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const NOTE: &str = "\x1b[1;32m";
+const GUTTER: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+fn severity_color(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => RED,
+        Severity::Warning => YELLOW,
+        Severity::Note => NOTE,
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn bold(self, text: &str) -> String {
+        self.paint(BOLD, text)
+    }
+}
+
+// Writes the location of `span` and, if the source is known, its first line
+// with the span underlined.
+fn snippet(
+    out: &mut String,
+    sources: &SourceMap,
+    span: Span,
+    gutter: usize,
+    underline_color: &str,
+    style: Style,
+) {
+    let file = span.file.and_then(|file| sources.get(file));
+    let name = file.map_or("<unknown>", |file| file.name.as_str());
+    let _ = writeln!(
+        out,
+        "{:gutter$}{} {}:{}:{}",
+        "",
+        style.paint(GUTTER, "-->"),
+        name,
+        span.line,
+        span.column
+    );
+
+    let line = match span.file.and_then(|file| sources.line(file, span.line)) {
+        Some(line) => line,
+        None => return,
+    };
+    let bar = style.paint(GUTTER, "|");
+    let _ = writeln!(out, "{:gutter$} {}", "", bar);
+    let _ = writeln!(
+        out,
+        "{} {} {}",
+        style.paint(GUTTER, &format!("{:>gutter$}", span.line)),
+        bar,
+        line
+    );
+
+    // Tabs are kept and wide characters (e.g. CJK) count twice, so the
+    // carets line up with the text above them.
+    let start = span.column.saturating_sub(1);
+    let end = if span.end_line == span.line {
+        span.end_column.saturating_sub(1)
+    } else {
+        line.chars().count()
+    };
+    let indent: String = line
+        .chars()
+        .take(start)
+        .map(|c| match c {
+            '\t' => String::from("\t"),
+            c => " ".repeat(c.width().unwrap_or(0)),
+        })
+        .collect();
+    let width: usize = line
+        .chars()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .map(|c| c.width().unwrap_or(1))
+        .sum();
+    let width = width.max(1);
+    let _ = writeln!(
+        out,
+        "{:gutter$} {} {}{}",
+        "",
+        bar,
+        indent,
+        style.paint(underline_color, &"^".repeat(width))
+    );
+}
+
+// How `Lox` writes errors to the diagnostics sink.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    // source snippets with the span underlined, see `Diagnostic::render`
+    #[default]
+    Human,
+    // one line per error, e.g. `[line 1:7] Error at ';': Expect expression.`
+    Short,
+    // one JSON object per line, see `Diagnostic::to_json`
+    Json,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(line: usize, column: usize, end_column: usize) -> Span {
+        Span {
+            file: Some(0),
+            start: 0,
+            end: 0,
+            line,
+            column,
+            end_line: line,
+            end_column,
+        }
+    }
+
+    fn sources() -> SourceMap {
+        let mut sources = SourceMap::new();
+        sources.add("a.lox", "var answer = 42;\r\nprint answer;\n");
+        sources
+    }
+
+    fn diagnostic(notes: Vec<Note>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            phase: Phase::Resolve,
+            message: String::from("Unused variable."),
+            span: Some(span(1, 5, 11)),
+            notes,
+        }
+    }
+
+    #[test]
+    fn underlines_the_whole_span() {
+        assert_eq!(
+            diagnostic(vec![]).render(&sources(), false),
+            "\
+warning[resolve]: Unused variable.
+ --> a.lox:1:5
+  |
+1 | var answer = 42;
+  |     ^^^^^^
+"
+        );
+    }
+
+    #[test]
+    fn notes_without_a_span_go_below_the_snippet() {
+        let notes = vec![Note::new("prefix it with '_' to silence this", None)];
+        assert!(diagnostic(notes)
+            .render(&sources(), false)
+            .ends_with("  |     ^^^^^^\n  = note: prefix it with '_' to silence this\n"));
+    }
+
+    #[test]
+    fn unknown_sources_only_show_the_location() {
+        assert_eq!(
+            diagnostic(vec![]).render(&SourceMap::new(), false),
+            "warning[resolve]: Unused variable.\n --> <unknown>:1:5\n"
+        );
+    }
+
+    #[test]
+    fn colors_only_when_asked() {
+        let plain = diagnostic(vec![]).render(&sources(), false);
+        let colored = diagnostic(vec![]).render(&sources(), true);
+        assert!(!plain.contains('\x1b'));
+        assert!(colored.starts_with("\x1b[1;33mwarning[resolve]\x1b[0m"));
+        assert!(colored.contains("\x1b[1;33m^^^^^^\x1b[0m"));
+    }
+
+    #[test]
+    fn json_escapes_strings() {
+        assert_eq!(
+            json_string("say \"hi\"\\\n\t\u{1}"),
+            "\"say \\\"hi\\\"\\\\\\n\\t\\u0001\""
+        );
+    }
+
+    #[test]
+    fn json_uses_null_for_unknown_locations() {
+        let diagnostic = Diagnostic {
+            span: None,
+            ..diagnostic(vec![])
+        };
+        assert_eq!(
+            diagnostic.to_json(&sources()),
+            "{\"severity\":\"warning\",\"phase\":\"resolve\",\"message\":\"Unused variable.\",\
+\"file\":null,\"line\":null,\"column\":null,\"span\":null,\"notes\":[]}"
+        );
+    }
+}
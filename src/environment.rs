@@ -36,23 +36,32 @@ impl Environment {
         }
 
         Err(Error::RuntimeError(
-            name.clone(),
+            Box::new(name.clone()),
             format!("Undefined variableble '{}'.", &name.lexeme),
         ))
     }
 
     pub fn get_at(&self, distance: usize, name: String) -> Result<Object> {
-        match self.ancestor(distance).values.borrow().get(&name) {
+        if distance > 0 {
+            return self
+                .enclosing_at(distance)
+                .borrow()
+                .get_at(distance - 1, name);
+        }
+        match self.values.borrow().get(&name) {
             Some(o) => Ok(o.clone()),
             None => unreachable!(),
         }
     }
 
     pub fn assign_at(&self, distance: usize, name: Token, value: Object) {
-        self.ancestor(distance)
-            .values
-            .borrow_mut()
-            .insert(name.lexeme, value);
+        if distance > 0 {
+            return self
+                .enclosing_at(distance)
+                .borrow()
+                .assign_at(distance - 1, name, value);
+        }
+        self.values.borrow_mut().insert(name.lexeme, value);
     }
 
     pub fn assign(&self, name: &Token, value: &Object) -> Result<()> {
@@ -68,27 +77,17 @@ impl Environment {
         }
 
         Err(Error::RuntimeError(
-            name.clone(),
+            Box::new(name.clone()),
             format!("Undefined variable '{}'", &name.lexeme),
         ))
     }
 
-    fn ancestor(&self, distance: usize) -> Environment {
-        let mut environment = self.clone();
-        for _i in 0..distance {
-            let enclosing = environment
-                .enclosing
-                .unwrap_or_else(|| panic!("No enclosing format at distance: {}", distance))
-                .borrow()
-                .clone();
-            environment = enclosing
-        }
-        environment
-    }
-
-    pub fn print_values(&self) {
-        for (key, value) in self.values.borrow().iter() {
-            println!("{}: {:?}", key, value);
-        }
+    // The enclosing environment, which the resolver guarantees exists for
+    // locals `distance` scopes out. Walking the shared chain rather than
+    // copying it means assignments land in the environment closures see.
+    fn enclosing_at(&self, distance: usize) -> &Rc<RefCell<Environment>> {
+        self.enclosing
+            .as_ref()
+            .unwrap_or_else(|| panic!("No enclosing environment at distance: {}", distance))
     }
 }
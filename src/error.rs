@@ -1,33 +1,97 @@
-use crate::{object::Object, token::Token, token_type::TokenType};
+use std::fmt;
 
+use crate::{
+    diagnostic::Note,
+    object::Object,
+    token::{Span, Token},
+    token_type::TokenType,
+};
+
+// Tokens and returned values are boxed to keep `Result`s small.
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum Error {
-    Return(Object),
-    ParseError(String),
-    RuntimeError(Token, String),
-    ResolveError(Token, String),
+    Return(Box<Object>),
+    // unwinding to the loop with the label, or to the innermost one
+    Break(Option<String>),
+    Continue(Option<String>),
+    ScanError(Span, String),
+    ParseError(Box<Token>, String),
+    RuntimeError(Box<Token>, String),
+    ResolveError(Box<Token>, String),
+    // raised by native functions, reported at the call site
+    NativeError(String),
+    // an error with extra information for the reader, e.g. where the
+    // offending variable was declared
+    WithNotes(Box<Error>, Vec<Note>),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-pub fn scanner_error(line: usize, message: &str) {
-    report(line, "", message)
-}
+impl Error {
+    // Where in the source the error happened, if known.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::ScanError(span, _) => Some(*span),
+            Error::ParseError(token, _)
+            | Error::ResolveError(token, _)
+            | Error::RuntimeError(token, _) => Some(token.span),
+            Error::Return(_) | Error::Break(_) | Error::Continue(_) | Error::NativeError(_) => None,
+            Error::WithNotes(error, _) => error.span(),
+        }
+    }
 
-pub fn parser_error(token: Token, message: &str) {
-    if token.token_type == TokenType::EOF {
-        report(token.line, " at end", message)
+    // Turns an error raised by host code into a runtime error at `token`.
+    pub fn at(self, token: &Token) -> Error {
+        match self {
+            Error::NativeError(message) => Error::RuntimeError(Box::new(token.clone()), message),
+            Error::WithNotes(error, notes) => Error::WithNotes(Box::new(error.at(token)), notes),
+            e => e,
+        }
     }
-    report(token.line, "", message)
-}
 
-fn report(line: usize, place: &str, message: &str) {
-    eprintln!("[line {}] Error {}: {}", line, place, message);
+    pub fn with_note(self, note: Note) -> Error {
+        match self {
+            Error::WithNotes(error, mut notes) => {
+                notes.push(note);
+                Error::WithNotes(error, notes)
+            }
+            error => Error::WithNotes(Box::new(error), vec![note]),
+        }
+    }
 }
 
-pub fn resolve_error(token: Token, message: &str) {
-    if token.token_type == TokenType::EOF {
-        report(token.line, " at end", message)
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Return(_) => write!(f, "Cannot return from top-level code."),
+            Error::Break(_) => write!(f, "Cannot use 'break' outside of a loop."),
+            Error::Continue(_) => write!(f, "Cannot use 'continue' outside of a loop."),
+            Error::ScanError(span, message) => {
+                write!(f, "[line {}:{}] Error: {}", span.line, span.column, message)
+            }
+            Error::ParseError(token, message) | Error::ResolveError(token, message) => {
+                if token.token_type == TokenType::EOF {
+                    write!(
+                        f,
+                        "[line {}:{}] Error at end: {}",
+                        token.span.line, token.span.column, message
+                    )
+                } else {
+                    write!(
+                        f,
+                        "[line {}:{}] Error at '{}': {}",
+                        token.span.line, token.span.column, token.lexeme, message
+                    )
+                }
+            }
+            Error::RuntimeError(token, message) => write!(
+                f,
+                "{}\n[line {}:{}]",
+                message, token.span.line, token.span.column
+            ),
+            Error::NativeError(message) => write!(f, "{}", message),
+            Error::WithNotes(error, _) => write!(f, "{}", error),
+        }
     }
-    report(token.line, "", message)
 }
@@ -1,4 +1,7 @@
-use crate::token::{self, Token};
+use crate::{
+    stmt::Stmt,
+    token::{self, Span, Token},
+};
 
 pub trait Visitor<T> {
     fn visit_binary(&mut self, left: &Expr, operator: &Token, right: &Expr) -> T;
@@ -13,13 +16,50 @@ pub trait Visitor<T> {
     fn visit_set(&mut self, object: &Expr, name: &Token, value: &Expr) -> T;
     fn visit_this(&mut self, keyword: &Token) -> T;
     fn visit_super(&mut self, keyword: &Token, method: &Token) -> T;
+    fn visit_interpolation(&mut self, parts: &[Expr]) -> T;
+    fn visit_compound_assign(
+        &mut self,
+        name: &Token,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> T;
+    fn visit_compound_set(
+        &mut self,
+        object: &Expr,
+        name: &Token,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> T;
+    fn visit_lambda(&mut self, keyword: &Token, params: &[Token], body: &[Stmt]) -> T;
+    fn visit_list(&mut self, elements: &[Expr]) -> T;
+    fn visit_map(&mut self, entries: &[(Expr, Expr)], brace: &Token) -> T;
+    fn visit_index(&mut self, object: &Expr, bracket: &Token, index: &Expr) -> T;
+    fn visit_set_index(&mut self, object: &Expr, bracket: &Token, index: &Expr, value: &Expr) -> T;
+    fn visit_slice(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        start: &Option<Box<Expr>>,
+        end: &Option<Box<Expr>>,
+    ) -> T;
+    fn visit_compound_set_index(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> T;
 }
 
 pub trait Acceptor<T> {
     fn accept(&self, visitor: &mut dyn Visitor<T>) -> T;
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone)]
 pub enum Expr {
     Binary {
         left: Box<Expr>,
@@ -36,9 +76,11 @@ pub enum Expr {
     },
     Grouping {
         expression: Box<Expr>,
+        span: Span,
     },
     Literal {
         value: token::Literal,
+        span: Span,
     },
     Logical {
         left: Box<Expr>,
@@ -69,6 +111,115 @@ pub enum Expr {
         paren: Token,
         arguments: Vec<Expr>,
     },
+    // `a += b`, and `++a` and `a++` with a `value` of 1. The operator is the
+    // `+=`, `++`, ... token; postfix forms evaluate to the old value.
+    CompoundAssign {
+        name: Token,
+        operator: Token,
+        value: Box<Expr>,
+        postfix: bool,
+    },
+    // the same for properties, e.g. `this.count += 1`
+    CompoundSet {
+        object: Box<Expr>,
+        name: Token,
+        operator: Token,
+        value: Box<Expr>,
+        postfix: bool,
+    },
+    // `[1, 2, 3]`
+    List {
+        elements: Vec<Expr>,
+        span: Span,
+    },
+    // `{"a": 1, "b": 2}`, where the brace is the closing one
+    Map {
+        entries: Vec<(Expr, Expr)>,
+        brace: Token,
+    },
+    // `xs[i]`, where the bracket is the closing one, like a call's paren
+    Index {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+    },
+    SetIndex {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
+    // `xs[start:end]`, either bound may be left out
+    Slice {
+        object: Box<Expr>,
+        bracket: Token,
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+    },
+    // `xs[i] += 1` and `xs[i]++`, see `CompoundAssign`
+    CompoundSetIndex {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        operator: Token,
+        value: Box<Expr>,
+        postfix: bool,
+    },
+    // `fun (a, b) { ... }`, or `(a, b) => a + b` whose body is a single
+    // `return`. The keyword is the `fun` or `=>` token.
+    Lambda {
+        keyword: Token,
+        params: Vec<Token>,
+        body: Vec<Stmt>,
+        // from `fun` or `(` to `}` or the end of the arrow's body
+        span: Span,
+    },
+    // `"a ${b} c"`: string literals alternating with the embedded expressions
+    Interpolation {
+        parts: Vec<Expr>,
+    },
+}
+
+impl Expr {
+    // The source range the expression was parsed from.
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                left.span().merge(right.span())
+            }
+            Expr::Unary { operator, right } => operator.span.merge(right.span()),
+            Expr::Get { object, name } => object.span().merge(name.span),
+            Expr::Grouping { span, .. } | Expr::Literal { span, .. } => *span,
+            Expr::Set { object, value, .. } => object.span().merge(value.span()),
+            Expr::Super { keyword, method } => keyword.span.merge(method.span),
+            Expr::This { keyword } => keyword.span,
+            Expr::Variable { name } => name.span,
+            Expr::Assign { name, value } | Expr::CompoundAssign { name, value, .. } => {
+                name.span.merge(value.span())
+            }
+            Expr::CompoundSet { object, value, .. } => object.span().merge(value.span()),
+            Expr::Call { callee, paren, .. } => callee.span().merge(paren.span),
+            Expr::List { span, .. } => *span,
+            Expr::Map { entries, brace } => {
+                entries.iter().fold(brace.span, |span, (key, value)| {
+                    span.merge(key.span()).merge(value.span())
+                })
+            }
+            Expr::Index {
+                object, bracket, ..
+            }
+            | Expr::Slice {
+                object, bracket, ..
+            } => object.span().merge(bracket.span),
+            Expr::SetIndex { object, value, .. } | Expr::CompoundSetIndex { object, value, .. } => {
+                object.span().merge(value.span())
+            }
+            Expr::Lambda { span, .. } => *span,
+            Expr::Interpolation { parts } => parts
+                .iter()
+                .fold(Span::default(), |span, part| span.merge(part.span())),
+        }
+    }
 }
 
 impl<T> Acceptor<T> for Expr {
@@ -79,8 +230,8 @@ impl<T> Acceptor<T> for Expr {
                 operator,
                 right,
             } => visitor.visit_binary(left, operator, right),
-            Expr::Grouping { expression } => visitor.visit_grouping(expression),
-            Expr::Literal { value } => visitor.visit_literal(value),
+            Expr::Grouping { expression, .. } => visitor.visit_grouping(expression),
+            Expr::Literal { value, .. } => visitor.visit_literal(value),
             Expr::Logical {
                 left,
                 operator,
@@ -102,6 +253,87 @@ impl<T> Acceptor<T> for Expr {
             } => visitor.visit_set(object, name, value),
             Expr::This { keyword } => visitor.visit_this(keyword),
             Expr::Super { keyword, method } => visitor.visit_super(keyword, method),
+            Expr::Interpolation { parts } => visitor.visit_interpolation(parts),
+            Expr::Lambda {
+                keyword,
+                params,
+                body,
+                ..
+            } => visitor.visit_lambda(keyword, params, body),
+            Expr::List { elements, .. } => visitor.visit_list(elements),
+            Expr::Map { entries, brace } => visitor.visit_map(entries, brace),
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => visitor.visit_index(object, bracket, index),
+            Expr::SetIndex {
+                object,
+                bracket,
+                index,
+                value,
+            } => visitor.visit_set_index(object, bracket, index, value),
+            Expr::Slice {
+                object,
+                bracket,
+                start,
+                end,
+            } => visitor.visit_slice(object, bracket, start, end),
+            Expr::CompoundSetIndex {
+                object,
+                bracket,
+                index,
+                operator,
+                value,
+                postfix,
+            } => {
+                visitor.visit_compound_set_index(object, bracket, index, operator, value, *postfix)
+            }
+            Expr::CompoundAssign {
+                name,
+                operator,
+                value,
+                postfix,
+            } => visitor.visit_compound_assign(name, operator, value, *postfix),
+            Expr::CompoundSet {
+                object,
+                name,
+                operator,
+                value,
+                postfix,
+            } => visitor.visit_compound_set(object, name, operator, value, *postfix),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::Parser, scanner::Scanner};
+
+    // The source text covered by the expression `source`.
+    fn spanned(source: &str) -> &str {
+        let (tokens, _) = Scanner::new(source.to_string()).scan_tokens();
+        let (expr, errors) = Parser::new(tokens).parse_expression();
+        assert!(errors.is_empty());
+        let span = expr.unwrap().span();
+        &source[span.start..span.end]
+    }
+
+    #[test]
+    fn spans_cover_the_whole_expression() {
+        assert_eq!(spanned(" 1 + 2 * x "), "1 + 2 * x");
+        assert_eq!(spanned("(a)"), "(a)");
+        assert_eq!(spanned("f(1, 2)"), "f(1, 2)");
+        assert_eq!(spanned("-a.b"), "-a.b");
+        assert_eq!(spanned("x = y or z"), "x = y or z");
+        assert_eq!(spanned("[1, 2][0]"), "[1, 2][0]");
+    }
+
+    #[test]
+    fn lambda_spans_cover_the_parameters_and_body() {
+        assert_eq!(spanned("(a, b) => a + b"), "(a, b) => a + b");
+        assert_eq!(spanned("() => 1"), "() => 1");
+        assert_eq!(spanned("fun (a) { return a; }"), "fun (a) { return a; }");
+        assert_eq!(spanned("fun () {}"), "fun () {}");
+    }
+}
@@ -1,14 +1,20 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, io::Write, rc::Rc, time::SystemTime};
 
 use crate::{
-    callable::{Clock, LoxCallable, LoxFunction},
+    arithmetic,
+    callable::{Arity, LoxCallable, LoxFunction, NativeFunction},
     environment::Environment,
     error::{Error, Result},
     expr::{self, Acceptor as ExprAcceptor, Expr},
     lox_class::LoxClass,
+    lox_instance::LoxInstance,
+    lox_list::LoxList,
+    lox_map::LoxMap,
     object::Object,
+    output::Sink,
+    range::Range,
     stmt::{self, Acceptor as StmtAcceptor, Stmt},
-    token::{Literal, Token},
+    token::{Literal, Span, Token},
     token_type::TokenType,
 };
 
@@ -16,38 +22,105 @@ use crate::{
 pub struct Interpreter {
     pub globals: Rc<RefCell<Environment>>,
     environment: Rc<RefCell<Environment>>,
-    locals: HashMap<Expr, usize>,
+    // how many scopes up each local variable, `this` and `super` is, by the
+    // span of its name token
+    locals: HashMap<Span, usize>,
+    output: Sink,
+    diagnostics: Sink,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Interpreter {
         let globals = Rc::new(RefCell::new(Environment::new(None)));
-        globals
-            .borrow_mut()
-            .define(String::from("clock"), &Object::Clock(Clock {}));
-        Interpreter {
+        let mut interpreter = Interpreter {
             globals: Rc::clone(&globals),
             environment: globals,
             locals: HashMap::new(),
-        }
+            output: Sink::stdout(),
+            diagnostics: Sink::stderr(),
+        };
+        interpreter.define_class(Range::class());
+        interpreter.define_native("clock", Arity::Fixed(0), |_, _| {
+            match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+                Ok(n) => Ok(Object::Literal(Literal::Isize(n.as_millis() as isize))),
+                Err(_) => Err(Error::NativeError(String::from(
+                    "SystemTime before UNIX EPOCH!",
+                ))),
+            }
+        });
+        interpreter
+    }
+
+    pub fn set_output<W: Write + 'static>(&mut self, output: W) {
+        self.output = Sink::new(output);
+    }
+
+    pub fn set_diagnostics<W: Write + 'static>(&mut self, diagnostics: W) {
+        self.diagnostics = Sink::new(diagnostics);
+    }
+
+    pub fn diagnostics(&self) -> Sink {
+        self.diagnostics.clone()
+    }
+
+    // Exposes a host function to scripts as a global.
+    pub fn define_native<F>(&mut self, name: &str, arity: Arity, function: F)
+    where
+        F: Fn(&mut Interpreter, Vec<Object>) -> Result<Object> + 'static,
+    {
+        let native = NativeFunction::new(name, arity, function);
+        self.globals
+            .borrow()
+            .define(name.to_string(), &Object::Native(native));
+    }
+
+    // Exposes a class built with `LoxClass::native` to scripts as a global.
+    pub fn define_class(&mut self, class: LoxClass) {
+        self.globals
+            .borrow()
+            .define(class.name.clone(), &Object::Class(class));
+    }
+
+    // Makes the command line arguments of a script visible as `argc` and `arg(n)`.
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.globals.borrow().define(
+            String::from("argc"),
+            &Object::Literal(Literal::Isize(args.len() as isize)),
+        );
+        self.define_native(
+            "arg",
+            Arity::Fixed(1),
+            move |_, arguments| match arguments[0] {
+                Object::Literal(Literal::Isize(i)) if i >= 0 => match args.get(i as usize) {
+                    Some(arg) => Ok(Object::Literal(Literal::String(arg.clone()))),
+                    None => Ok(Object::Literal(Literal::None)),
+                },
+                _ => Err(Error::NativeError(String::from(
+                    "Argument index must be a non-negative integer.",
+                ))),
+            },
+        );
     }
 
     pub fn interpret(&mut self, statements: Vec<Stmt>) -> Result<()> {
         for statement in statements {
-            match self.execute(&statement) {
-                Ok(_) => {}
-                Err(r) => eprintln!("{:?}", r),
-            }
+            self.execute(&statement)?;
         }
         Ok(())
     }
 
-    pub fn resolve(&mut self, expr: Expr, depth: usize) -> Result<()> {
-        self.locals.insert(expr, depth);
+    pub fn resolve(&mut self, name: &Token, depth: usize) -> Result<()> {
+        self.locals.insert(name.span, depth);
         Ok(())
     }
 
-    fn evaluate(&mut self, expr: &Expr) -> Result<Object> {
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Object> {
         expr.accept(self)
     }
 
@@ -55,24 +128,24 @@ impl Interpreter {
         stmt.accept(self)
     }
 
-    fn is_truthy(&self, object: Object) -> bool {
+    pub fn is_truthy(&self, object: Object) -> bool {
         match object {
             Object::Literal(literal) => match literal {
                 Literal::None => false,
                 Literal::Bool(b) => b,
                 _ => true,
             },
-            // FIXME
-            _ => false,
+            // functions, classes and instances
+            _ => true,
         }
     }
 
-    fn look_up_variable(&mut self, name: &Token, expr: &Expr) -> Result<Object> {
-        match self.locals.get(&expr) {
+    fn look_up_variable(&mut self, name: &Token) -> Result<Object> {
+        match self.locals.get(&name.span) {
             Some(distance) => self
                 .environment
                 .borrow()
-                .get_at(distance.clone(), name.lexeme.clone()),
+                .get_at(*distance, name.lexeme.clone()),
             _ => self.globals.borrow().get(name),
         }
     }
@@ -85,13 +158,204 @@ impl Interpreter {
                 (Literal::String(a), Literal::String(b)) => a == b,
                 (Literal::Isize(a), Literal::Isize(b)) => a == b,
                 (Literal::Float(a), Literal::Float(b)) => a == b,
+                (Literal::BigInt(a), Literal::BigInt(b)) => a == b,
+                (Literal::Isize(a), Literal::Float(b)) | (Literal::Float(b), Literal::Isize(a)) => {
+                    a as f64 == b
+                }
+                (Literal::BigInt(a), Literal::Float(b))
+                | (Literal::Float(b), Literal::BigInt(a)) => a.to_f64() == b,
                 _ => false,
             },
+            (Object::List(a), Object::List(b)) => a.ptr_eq(&b),
+            (Object::Map(a), Object::Map(b)) => a.ptr_eq(&b),
             // FIXME
             _ => false,
         }
     }
 
+    // Calls a function or class, e.g. a callback passed to a native function.
+    // Errors are reported at the call site of the native function.
+    pub fn call_object(&mut self, callee: &Object, arguments: Vec<Object>) -> Result<Object> {
+        match callee {
+            Object::Func(func) => self.call(func, arguments),
+            Object::Native(func) => self.call(func, arguments),
+            Object::Class(class) => self.call(class, arguments),
+            _ => Err(Error::NativeError(String::from(
+                "Can only call functions and classes.",
+            ))),
+        }
+    }
+
+    fn call(&mut self, callable: &dyn LoxCallable, arguments: Vec<Object>) -> Result<Object> {
+        if !callable.arity().accepts(arguments.len()) {
+            return Err(Error::NativeError(format!(
+                "Expected {} arguments but got {}.",
+                callable.arity(),
+                arguments.len()
+            )));
+        }
+        callable.call(self, arguments)
+    }
+
+    fn index(&self, object: Object, bracket: &Token, index: &Object) -> Result<Object> {
+        match object {
+            Object::List(list) => list.index(index).map_err(|e| e.at(bracket)),
+            Object::Map(map) => map.index(index).map_err(|e| e.at(bracket)),
+            _ => Err(Error::RuntimeError(
+                Box::new(bracket.clone()),
+                String::from("Only lists and maps can be indexed."),
+            )),
+        }
+    }
+
+    fn set_index(
+        &self,
+        object: Object,
+        bracket: &Token,
+        index: &Object,
+        value: Object,
+    ) -> Result<()> {
+        match object {
+            Object::List(list) => list.set_index(index, value).map_err(|e| e.at(bracket)),
+            Object::Map(map) => map.set_index(index, value).map_err(|e| e.at(bracket)),
+            _ => Err(Error::RuntimeError(
+                Box::new(bracket.clone()),
+                String::from("Only lists and maps can be indexed."),
+            )),
+        }
+    }
+
+    fn binary(&self, operator: &Token, left: Object, right: Object) -> Result<Object> {
+        match (operator.token_type, left, right) {
+            (TokenType::BANGEQUAL, left, right) => {
+                Ok(Object::Literal(Literal::Bool(!self.is_equal(left, right))))
+            }
+            (TokenType::EQUALEQUAL, left, right) => {
+                Ok(Object::Literal(Literal::Bool(self.is_equal(left, right))))
+            }
+            (_, Object::Literal(left), Object::Literal(right)) => {
+                arithmetic::binary(operator, &left, &right).map(Object::Literal)
+            }
+            _ => Err(Error::RuntimeError(
+                Box::new(operator.clone()),
+                String::from("Operands must be numbers."),
+            )),
+        }
+    }
+
+    fn assign_variable(&mut self, name: &Token, value: &Object) -> Result<()> {
+        match self.locals.get(&name.span) {
+            Some(distance) => {
+                self.environment
+                    .borrow()
+                    .assign_at(*distance, name.clone(), value.clone());
+                Ok(())
+            }
+            None => self.globals.borrow().assign(name, value),
+        }
+    }
+
+    // Applies `a += b` style `operator` to the current value of the target
+    // and `value`, which is evaluated after the target was read.
+    fn compound(&mut self, operator: &Token, current: Object, value: &Expr) -> Result<Object> {
+        let value = self.evaluate(value)?;
+        let token_type = match operator.token_type {
+            TokenType::PLUSEQUAL | TokenType::PLUSPLUS => TokenType::PLUS,
+            TokenType::MINUSEQUAL | TokenType::MINUSMINUS => TokenType::MINUS,
+            TokenType::STAREQUAL => TokenType::STAR,
+            TokenType::SLASHEQUAL => TokenType::SLASH,
+            token_type => token_type,
+        };
+        let operator = Token {
+            token_type,
+            ..operator.clone()
+        };
+        self.binary(&operator, current, value)
+    }
+
+    // Runs one pass of the body of the loop labeled `label`, returning
+    // whether the loop goes on. An unlabeled `break` or `continue` is for the
+    // innermost loop.
+    fn loop_body<F>(&mut self, label: &Option<Token>, body: F) -> Result<bool>
+    where
+        F: FnOnce(&mut Interpreter) -> Result<()>,
+    {
+        let is_target = |target: &Option<String>| match target {
+            Some(target) => label.as_ref().is_some_and(|label| &label.lexeme == target),
+            None => true,
+        };
+        match body(self) {
+            Ok(()) => Ok(true),
+            Err(Error::Break(target)) if is_target(&target) => Ok(false),
+            Err(Error::Continue(target)) if is_target(&target) => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Calls `each` with the elements of a list, the keys of a map, the
+    // characters of a string or the integers of a range, until it returns
+    // false. Instances of other classes are iterated with the object their
+    // `iter()` method returns, whose `next()` returns nil once it's done.
+    fn iterate<F>(&mut self, iterable: Object, keyword: &Token, mut each: F) -> Result<()>
+    where
+        F: FnMut(&mut Interpreter, Object) -> Result<bool>,
+    {
+        let values: Box<dyn Iterator<Item = Object>> = match iterable {
+            Object::List(list) => Box::new(list.elements().into_iter()),
+            Object::Map(map) => Box::new(map.entries().into_iter().map(|(key, _)| key)),
+            Object::Literal(Literal::String(s)) => Box::new(
+                s.chars()
+                    .map(|c| Object::Literal(Literal::String(c.to_string())))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ),
+            Object::Instance(instance) => match instance.data::<Range>() {
+                Some(range) => Box::new(range.iter().map(|i| Object::Literal(Literal::Isize(i)))),
+                None => return self.iterate_protocol(instance, keyword, each),
+            },
+            _ => return Err(not_iterable(keyword)),
+        };
+        for value in values {
+            if !each(self, value)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn iterate_protocol<F>(
+        &mut self,
+        instance: LoxInstance,
+        keyword: &Token,
+        mut each: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&mut Interpreter, Object) -> Result<bool>,
+    {
+        let iter = instance
+            .method("iter")
+            .ok_or_else(|| not_iterable(keyword))?;
+        let next = match self.call_object(&iter, vec![]).map_err(|e| e.at(keyword))? {
+            Object::Instance(iterator) => iterator.method("next"),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            Error::RuntimeError(
+                Box::new(keyword.clone()),
+                String::from("'iter()' must return an instance with a 'next' method."),
+            )
+        })?;
+        loop {
+            let value = self.call_object(&next, vec![]).map_err(|e| e.at(keyword))?;
+            if let Object::Literal(Literal::None) = value {
+                return Ok(());
+            }
+            if !each(self, value)? {
+                return Ok(());
+            }
+        }
+    }
+
     pub fn execute_block(&mut self, statements: &[Stmt], environment: Environment) -> Result<()> {
         let previous = self.environment.clone();
         self.environment = Rc::new(RefCell::new(environment));
@@ -114,131 +378,9 @@ impl expr::Visitor<Result<Object>> for Interpreter {
         Ok(Object::Literal(expr.clone()))
     }
     fn visit_binary(&mut self, left: &Expr, operator: &Token, right: &Expr) -> Result<Object> {
-        use crate::token::Literal::{Bool, Float, Isize, None, String as LString};
-        use crate::token_type::TokenType::{
-            BANGEQUAL, EQUALEQUAL, GREATER, GREATEREQUAL, LESS, LESSEQUAL, MINUS, PLUS, SLASH, STAR,
-        };
         let left = self.evaluate(left)?;
         let right = self.evaluate(right)?;
-
-        match operator.token_type {
-            GREATER => match (left, right) {
-                (Object::Literal(oll), Object::Literal(olr)) => match (oll, olr) {
-                    (Isize(l), Isize(r)) => Ok(Object::Literal(Bool(l > r))),
-                    (Isize(l), Float(r)) => Ok(Object::Literal(Bool(l as f64 > r))),
-                    (Float(l), Isize(r)) => Ok(Object::Literal(Bool(l > r as f64))),
-                    (Float(l), Float(r)) => Ok(Object::Literal(Bool(l > r))),
-                    _ => Err(Error::RuntimeError(
-                        operator.clone(),
-                        String::from("Operands must be numbers."),
-                    )),
-                },
-                _ => unreachable!(),
-            },
-
-            GREATEREQUAL => match (left, right) {
-                (Object::Literal(oll), Object::Literal(olr)) => match (oll, olr) {
-                    (Isize(l), Isize(r)) => Ok(Object::Literal(Bool(l >= r))),
-                    (Isize(l), Float(r)) => Ok(Object::Literal(Bool(l as f64 >= r))),
-                    (Float(l), Isize(r)) => Ok(Object::Literal(Bool(l >= r as f64))),
-                    (Float(l), Float(r)) => Ok(Object::Literal(Bool(l >= r))),
-                    _ => Err(Error::RuntimeError(
-                        operator.clone(),
-                        String::from("Operands must be numbers."),
-                    )),
-                },
-                _ => unreachable!(),
-            },
-
-            LESS => match (left, right) {
-                (Object::Literal(oll), Object::Literal(olr)) => match (oll, olr) {
-                    (Isize(l), Isize(r)) => Ok(Object::Literal(Bool(l < r))),
-                    (Isize(l), Float(r)) => Ok(Object::Literal(Bool((l as f64) < r))),
-                    (Float(l), Isize(r)) => Ok(Object::Literal(Bool(l < r as f64))),
-                    (Float(l), Float(r)) => Ok(Object::Literal(Bool(l < r))),
-                    _ => Err(Error::RuntimeError(
-                        operator.clone(),
-                        String::from("Operands must be numbers."),
-                    )),
-                },
-                _ => unreachable!(),
-            },
-
-            LESSEQUAL => match (left, right) {
-                (Object::Literal(oll), Object::Literal(olr)) => match (oll, olr) {
-                    (Isize(l), Isize(r)) => Ok(Object::Literal(Bool(l <= r))),
-                    (Isize(l), Float(r)) => Ok(Object::Literal(Bool((l as f64) <= r))),
-                    (Float(l), Isize(r)) => Ok(Object::Literal(Bool(l <= r as f64))),
-                    (Float(l), Float(r)) => Ok(Object::Literal(Bool(l <= r))),
-                    _ => Err(Error::RuntimeError(
-                        operator.clone(),
-                        String::from("Operands must be numbers."),
-                    )),
-                },
-                _ => unreachable!(),
-            },
-
-            MINUS => match (left, right) {
-                (Object::Literal(oll), Object::Literal(olr)) => match (oll, olr) {
-                    (Isize(l), Isize(r)) => Ok(Object::Literal(Isize(l - r))),
-                    (Isize(l), Float(r)) => Ok(Object::Literal(Float((l as f64) - r))),
-                    (Float(l), Isize(r)) => Ok(Object::Literal(Float(l - r as f64))),
-                    (Float(l), Float(r)) => Ok(Object::Literal(Float(l - r))),
-                    _ => Err(Error::RuntimeError(
-                        operator.clone(),
-                        String::from("Operands must be numbers."),
-                    )),
-                },
-                _ => unreachable!(),
-            },
-
-            PLUS => match (left, right) {
-                (Object::Literal(oll), Object::Literal(olr)) => match (oll, olr) {
-                    (Isize(l), Isize(r)) => Ok(Object::Literal(Isize(l + r))),
-                    (Isize(l), Float(r)) => Ok(Object::Literal(Float((l as f64) + r))),
-                    (Float(l), Isize(r)) => Ok(Object::Literal(Float(l + r as f64))),
-                    (Float(l), Float(r)) => Ok(Object::Literal(Float(l + r))),
-                    (LString(l), LString(r)) => Ok(Object::Literal(LString(format!("{}{}", l, r)))),
-                    _ => Err(Error::RuntimeError(
-                        operator.clone(),
-                        String::from("Operands must be numbers."),
-                    )),
-                },
-                _ => unreachable!(),
-            },
-
-            SLASH => match (left, right) {
-                (Object::Literal(oll), Object::Literal(olr)) => match (oll, olr) {
-                    (Isize(l), Isize(r)) => Ok(Object::Literal(Isize(l / r))),
-                    (Isize(l), Float(r)) => Ok(Object::Literal(Float((l as f64) / r))),
-                    (Float(l), Isize(r)) => Ok(Object::Literal(Float(l / r as f64))),
-                    (Float(l), Float(r)) => Ok(Object::Literal(Float(l / r))),
-                    _ => Err(Error::RuntimeError(
-                        operator.clone(),
-                        String::from("Operands must be numbers."),
-                    )),
-                },
-                _ => unreachable!(),
-            },
-
-            STAR => match (left, right) {
-                (Object::Literal(oll), Object::Literal(olr)) => match (oll, olr) {
-                    (Isize(l), Isize(r)) => Ok(Object::Literal(Isize(l * r))),
-                    (Isize(l), Float(r)) => Ok(Object::Literal(Float((l as f64) * r))),
-                    (Float(l), Isize(r)) => Ok(Object::Literal(Float(l * r as f64))),
-                    (Float(l), Float(r)) => Ok(Object::Literal(Float(l * r))),
-                    _ => Err(Error::RuntimeError(
-                        operator.clone(),
-                        String::from("Operands must be numbers."),
-                    )),
-                },
-                _ => unreachable!(),
-            },
-
-            BANGEQUAL => Ok(Object::Literal(Bool(!self.is_equal(left, right)))),
-            EQUALEQUAL => Ok(Object::Literal(Bool(self.is_equal(left, right)))),
-            _ => Ok(Object::Literal(None)),
-        }
+        self.binary(operator, left, right)
     }
 
     fn visit_grouping(&mut self, expression: &Expr) -> Result<Object> {
@@ -246,46 +388,33 @@ impl expr::Visitor<Result<Object>> for Interpreter {
     }
 
     fn visit_unary(&mut self, operator: &Token, right: &Expr) -> Result<Object> {
-        use crate::token::Literal::{Bool, Float, Isize, None};
-
         let right = self.evaluate(right)?;
         match (operator.token_type, right) {
-            (TokenType::MINUS, Object::Literal(lit)) => match lit {
-                Isize(r) => Ok(Object::Literal(Isize(-r))),
-                Float(r) => Ok(Object::Literal(Float(-r))),
-                _ => Err(Error::RuntimeError(
-                    operator.clone(),
-                    String::from("Operand must be a number."),
-                )),
-            },
-            (TokenType::BANG, Object::Literal(lit)) => {
-                Ok(Object::Literal(Bool(!self.is_truthy(Object::Literal(lit)))))
+            (TokenType::MINUS, Object::Literal(lit)) => {
+                arithmetic::negate(operator, &lit).map(Object::Literal)
+            }
+            (TokenType::MINUS, _) => Err(Error::RuntimeError(
+                Box::new(operator.clone()),
+                String::from("Operand must be a number."),
+            )),
+            (TokenType::TILDE, Object::Literal(lit)) => {
+                arithmetic::bitwise_not(operator, &lit).map(Object::Literal)
             }
-            _ => Ok(Object::Literal(None)),
+            (TokenType::TILDE, _) => Err(Error::RuntimeError(
+                Box::new(operator.clone()),
+                String::from("Operand of '~' must be an integer."),
+            )),
+            (_, right) => Ok(Object::Literal(Literal::Bool(!self.is_truthy(right)))),
         }
     }
 
     fn visit_variable(&mut self, name: &Token) -> Result<Object> {
-        let expr = Expr::Variable { name: name.clone() };
-        self.look_up_variable(name, &expr)
+        self.look_up_variable(name)
     }
 
     fn visit_assign(&mut self, name: &Token, value: &Expr) -> Result<Object> {
         let evaluated_value = self.evaluate(value)?;
-        let expr = Expr::Assign {
-            name: name.clone(),
-            value: Box::new(value.clone()),
-        };
-        match self.locals.get(&expr) {
-            Some(distance) => {
-                self.environment.borrow_mut().assign_at(
-                    distance.clone(),
-                    name.clone(),
-                    evaluated_value.clone(),
-                );
-            }
-            None => self.globals.borrow_mut().assign(name, &evaluated_value)?,
-        }
+        self.assign_variable(name, &evaluated_value)?;
         Ok(evaluated_value)
     }
 
@@ -309,59 +438,18 @@ impl expr::Visitor<Result<Object>> for Interpreter {
         for argument in arguments {
             evaluated_args.push(self.evaluate(argument)?)
         }
-        match callee {
-            Object::Func(func) => {
-                if evaluated_args.len() != func.arity() {
-                    return Err(Error::RuntimeError(
-                        paren.clone(),
-                        format!(
-                            "Expected {} arguments but got {}.",
-                            func.arity(),
-                            evaluated_args.len()
-                        ),
-                    ));
-                }
-                Ok(func.call(self, evaluated_args)?)
-            }
-            Object::Clock(func) => {
-                if evaluated_args.len() != func.arity() {
-                    return Err(Error::RuntimeError(
-                        paren.clone(),
-                        format!(
-                            "Expected {} arguments but got {}.",
-                            func.arity(),
-                            evaluated_args.len()
-                        ),
-                    ));
-                }
-                Ok(func.call(self, evaluated_args)?)
-            }
-            Object::Class(class) => {
-                if evaluated_args.len() != class.arity() {
-                    return Err(Error::RuntimeError(
-                        paren.clone(),
-                        format!(
-                            "Expected {} arguments but got {}.",
-                            class.arity(),
-                            evaluated_args.len()
-                        ),
-                    ));
-                }
-                Ok(class.call(self, evaluated_args)?)
-            }
-            _ => Err(Error::RuntimeError(
-                paren.clone(),
-                String::from("Can only call functions and classes."),
-            )),
-        }
+        self.call_object(&callee, evaluated_args)
+            .map_err(|e| e.at(paren))
     }
 
     fn visit_get(&mut self, object: &Expr, name: &Token) -> Result<Object> {
         let evaluated_object = self.evaluate(object)?;
         match evaluated_object {
             Object::Instance(mut instance) => Ok(instance.get(name)?),
+            Object::List(list) => list.get(name),
+            Object::Map(map) => map.get(name),
             _ => Err(Error::RuntimeError(
-                name.clone(),
+                Box::new(name.clone()),
                 String::from("Only instances have properties."),
             )),
         }
@@ -372,11 +460,11 @@ impl expr::Visitor<Result<Object>> for Interpreter {
         match evaluated_object {
             Object::Instance(mut instance) => {
                 let evaluated_value = self.evaluate(value)?;
-                instance.set(name, &evaluated_value);
+                instance.set(name, &evaluated_value)?;
                 Ok(evaluated_value)
             }
             _ => Err(Error::RuntimeError(
-                name.clone(),
+                Box::new(name.clone()),
                 String::from("Only instances have fields."),
             )),
         }
@@ -385,51 +473,188 @@ impl expr::Visitor<Result<Object>> for Interpreter {
     fn visit_super(&mut self, keyword: &Token, method: &Token) -> Result<Object> {
         let distance = self
             .locals
-            .get(&Expr::Super {
-                keyword: keyword.clone(),
-                method: method.clone(),
-            })
-            .expect(&format!("super found on locals: {:?}", self.locals));
+            .get(&keyword.span)
+            .unwrap_or_else(|| panic!("super found on locals: {:?}", self.locals));
         let object_super = self
             .environment
             .borrow()
-            .get_at(distance.clone(), "super".to_string())?;
+            .get_at(*distance, "super".to_string())?;
         if let Object::Class(superclass) = object_super {
             let this = self
                 .environment
                 .borrow()
                 .get_at(distance - 1, "this".to_string())?;
             if let Object::Instance(object) = this {
-                if let Some(method) = superclass.find_method(method.lexeme.clone()) {
-                    return Ok(Object::Func(method.bind(object)));
-                }
-                return Err(Error::RuntimeError(
-                    method.clone(),
-                    format!("Undefined propertiesperty '{}' '.", method.lexeme),
-                ));
+                let property = superclass
+                    .find_super_property(&method.lexeme, &object)
+                    .map_err(|e| e.at(method))?;
+                return property.ok_or_else(|| {
+                    Error::RuntimeError(
+                        Box::new(method.clone()),
+                        format!("Undefined property '{}'", method.lexeme),
+                    )
+                });
             }
             return Err(Error::RuntimeError(
-                method.clone(),
+                Box::new(method.clone()),
                 format!("'this' should be instance but actually: {}'.", this),
             ));
         }
         Err(Error::RuntimeError(
-            method.clone(),
+            Box::new(method.clone()),
             format!("'super' should be class but actually: {}'.", object_super),
         ))
     }
 
     fn visit_this(&mut self, keyword: &Token) -> Result<Object> {
-        let expr = Expr::This {
-            keyword: keyword.clone(),
+        self.look_up_variable(keyword)
+    }
+
+    fn visit_compound_assign(
+        &mut self,
+        name: &Token,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> Result<Object> {
+        let current = self.look_up_variable(name)?;
+        let result = self.compound(operator, current.clone(), value)?;
+        self.assign_variable(name, &result)?;
+        Ok(if postfix { current } else { result })
+    }
+
+    fn visit_compound_set(
+        &mut self,
+        object: &Expr,
+        name: &Token,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> Result<Object> {
+        // the object is evaluated once, so `f().count += 1` calls `f` once
+        match self.evaluate(object)? {
+            Object::Instance(mut instance) => {
+                let current = instance.get(name)?;
+                let result = self.compound(operator, current.clone(), value)?;
+                instance.set(name, &result)?;
+                Ok(if postfix { current } else { result })
+            }
+            _ => Err(Error::RuntimeError(
+                Box::new(name.clone()),
+                String::from("Only instances have fields."),
+            )),
+        }
+    }
+
+    fn visit_lambda(&mut self, keyword: &Token, params: &[Token], body: &[Stmt]) -> Result<Object> {
+        let name = Token {
+            lexeme: String::from("lambda"),
+            ..keyword.clone()
+        };
+        Ok(Object::Func(LoxFunction::new(
+            name,
+            params.to_vec(),
+            body.to_vec(),
+            Rc::clone(&self.environment),
+            false,
+        )))
+    }
+
+    fn visit_list(&mut self, elements: &[Expr]) -> Result<Object> {
+        let mut evaluated = vec![];
+        for element in elements {
+            evaluated.push(self.evaluate(element)?);
+        }
+        Ok(Object::List(LoxList::new(evaluated)))
+    }
+
+    fn visit_map(&mut self, entries: &[(Expr, Expr)], brace: &Token) -> Result<Object> {
+        let map = LoxMap::default();
+        for (key, value) in entries {
+            let key = self.evaluate(key)?;
+            let value = self.evaluate(value)?;
+            map.set_index(&key, value).map_err(|e| e.at(brace))?;
+        }
+        Ok(Object::Map(map))
+    }
+
+    fn visit_index(&mut self, object: &Expr, bracket: &Token, index: &Expr) -> Result<Object> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        self.index(object, bracket, &index)
+    }
+
+    fn visit_set_index(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> Result<Object> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        let value = self.evaluate(value)?;
+        self.set_index(object, bracket, &index, value.clone())?;
+        Ok(value)
+    }
+
+    fn visit_slice(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        start: &Option<Box<Expr>>,
+        end: &Option<Box<Expr>>,
+    ) -> Result<Object> {
+        let object = self.evaluate(object)?;
+        let start = match start {
+            Some(start) => Some(self.evaluate(start)?),
+            None => None,
         };
-        self.look_up_variable(keyword, &expr)
+        let end = match end {
+            Some(end) => Some(self.evaluate(end)?),
+            None => None,
+        };
+        match object {
+            Object::List(list) => list
+                .slice(start.as_ref(), end.as_ref())
+                .map(Object::List)
+                .map_err(|e| e.at(bracket)),
+            _ => Err(Error::RuntimeError(
+                Box::new(bracket.clone()),
+                String::from("Only lists can be sliced."),
+            )),
+        }
+    }
+
+    fn visit_compound_set_index(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> Result<Object> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        let current = self.index(object.clone(), bracket, &index)?;
+        let result = self.compound(operator, current.clone(), value)?;
+        self.set_index(object, bracket, &index, result.clone())?;
+        Ok(if postfix { current } else { result })
+    }
+
+    fn visit_interpolation(&mut self, parts: &[Expr]) -> Result<Object> {
+        let mut string = String::new();
+        for part in parts {
+            string.push_str(&self.evaluate(part)?.to_string());
+        }
+        Ok(Object::Literal(Literal::String(string)))
     }
 }
 
 impl stmt::Visitor<Result<()>> for Interpreter {
     fn visit_expression_stmt(&mut self, expression: &Expr) -> Result<()> {
-        let result = self.evaluate(expression)?;
+        let _result = self.evaluate(expression)?;
         // if self.environment.borrow().is_repl {
         //     println!("{}", result);
         // }
@@ -438,8 +663,10 @@ impl stmt::Visitor<Result<()>> for Interpreter {
 
     fn visit_print_stmt(&mut self, expression: &Expr) -> Result<()> {
         let value = self.evaluate(expression)?;
-        println!("{}", value);
-        Ok(())
+        // written in one piece so callback sinks see whole lines
+        self.output
+            .write_all(format!("{}\n", value).as_bytes())
+            .map_err(|e| Error::NativeError(e.to_string()))
     }
 
     fn visit_var_stmt(&mut self, name: &Token, initializer: &Expr) -> Result<()> {
@@ -470,26 +697,64 @@ impl stmt::Visitor<Result<()>> for Interpreter {
         let evaluated = self.evaluate(condition)?;
         if self.is_truthy(evaluated) {
             self.execute(then_branch)?
-        }
-        match else_branch {
-            Some(eb) => self.execute(&*eb)?,
-            None => {}
+        } else if let Some(eb) = else_branch {
+            self.execute(eb)?
         }
         Ok(())
     }
 
-    fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> Result<()> {
+    fn visit_while_stmt(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: &Option<Box<Expr>>,
+        label: &Option<Token>,
+    ) -> Result<()> {
         loop {
             let evaluated_condition = self.evaluate(condition)?;
-            if self.is_truthy(evaluated_condition) {
-                self.execute(body)?
-            } else {
+            if !self.is_truthy(evaluated_condition) {
+                break;
+            }
+            if !self.loop_body(label, |interpreter| interpreter.execute(body))? {
                 break;
             }
+            if let Some(increment) = increment {
+                self.evaluate(increment)?;
+            }
         }
         Ok(())
     }
 
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &Token,
+        keyword: &Token,
+        iterable: &Expr,
+        body: &Stmt,
+        label: &Option<Token>,
+    ) -> Result<()> {
+        let iterable = self.evaluate(iterable)?;
+        self.iterate(iterable, keyword, |interpreter, value| {
+            let environment = Environment::new(Some(Rc::clone(&interpreter.environment)));
+            environment.define(name.lexeme.clone(), &value);
+            interpreter.loop_body(label, |interpreter| {
+                interpreter.execute_block(std::slice::from_ref(body), environment)
+            })
+        })
+    }
+
+    fn visit_break_stmt(&mut self, _keyword: &Token, label: &Option<Token>) -> Result<()> {
+        Err(Error::Break(
+            label.as_ref().map(|label| label.lexeme.clone()),
+        ))
+    }
+
+    fn visit_continue_stmt(&mut self, _keyword: &Token, label: &Option<Token>) -> Result<()> {
+        Err(Error::Continue(
+            label.as_ref().map(|label| label.lexeme.clone()),
+        ))
+    }
+
     fn visit_function_stmt(&mut self, name: &Token, params: &[Token], body: &[Stmt]) -> Result<()> {
         use super::callable::LoxFunction;
         let function = Object::Func(LoxFunction::new(
@@ -507,13 +772,13 @@ impl stmt::Visitor<Result<()>> for Interpreter {
 
     fn visit_return_stmt(&mut self, _keyword: &Token, v: &Expr) -> Result<()> {
         let evaluated_value = match v {
-            Expr::Literal { value } => match value {
-                Literal::None => Object::Literal(Literal::None),
-                _ => self.evaluate(&v)?,
-            },
-            _ => self.evaluate(&v)?,
+            Expr::Literal {
+                value: Literal::None,
+                ..
+            } => Object::Literal(Literal::None),
+            _ => self.evaluate(v)?,
         };
-        Err(Error::Return(evaluated_value))
+        Err(Error::Return(Box::new(evaluated_value)))
     }
 
     fn visit_class_stmt(
@@ -528,7 +793,7 @@ impl stmt::Visitor<Result<()>> for Interpreter {
                 _ => {
                     if let Expr::Variable { name: scname } = sc {
                         return Err(Error::RuntimeError(
-                            scname.clone(),
+                            Box::new(scname.clone()),
                             "Superclass must be a class.".to_string(),
                         ));
                     }
@@ -562,6 +827,7 @@ impl stmt::Visitor<Result<()>> for Interpreter {
                     name: func_name,
                     params,
                     body,
+                    ..
                 } => {
                     let function = LoxFunction::new(
                         func_name.clone(),
@@ -594,3 +860,12 @@ impl stmt::Visitor<Result<()>> for Interpreter {
         Ok(())
     }
 }
+
+fn not_iterable(keyword: &Token) -> Error {
+    Error::RuntimeError(
+        Box::new(keyword.clone()),
+        String::from(
+            "Can only iterate over lists, maps, strings, ranges and instances with an 'iter' method.",
+        ),
+    )
+}
@@ -0,0 +1,31 @@
+//! A tree-walking interpreter for the Lox language from "Crafting Interpreters".
+//!
+//! [`Lox`] is the entry point for embedding; the individual phases
+//! ([`scanner::Scanner`], [`parser::Parser`], [`resolver::Resolver`] and
+//! [`interpreter::Interpreter`]) are public for tools that need them.
+
+pub mod arithmetic;
+pub mod ast_printer;
+pub mod bigint;
+pub mod callable;
+pub mod diagnostic;
+pub mod environment;
+pub mod error;
+pub mod expr;
+pub mod interpreter;
+mod lox;
+pub mod lox_class;
+pub mod lox_instance;
+pub mod lox_list;
+pub mod lox_map;
+pub mod object;
+pub mod output;
+pub mod parser;
+pub mod range;
+pub mod resolver;
+pub mod scanner;
+pub mod stmt;
+pub mod token;
+pub mod token_type;
+
+pub use lox::Lox;
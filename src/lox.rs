@@ -0,0 +1,179 @@
+use std::io::Write;
+
+use crate::{
+    callable::Arity,
+    diagnostic::{Diagnostic, ErrorFormat, SourceMap},
+    error::{Error, Result},
+    interpreter::Interpreter,
+    lox_class::LoxClass,
+    object::Object,
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    token::Literal,
+};
+
+/// An interpreter session. Globals defined by one call to `run_source` stay
+/// visible to the following ones, the same way the REPL works.
+#[derive(Debug, Default)]
+pub struct Lox {
+    interpreter: Interpreter,
+    sources: SourceMap,
+    error_format: ErrorFormat,
+    color: bool,
+}
+
+impl Lox {
+    pub fn new() -> Lox {
+        Lox {
+            interpreter: Interpreter::new(),
+            ..Lox::default()
+        }
+    }
+
+    /// Scans, parses, resolves and runs a whole program. Errors are written
+    /// to the diagnostics sink and the first one is returned.
+    pub fn run_source(&mut self, source: &str) -> Result<()> {
+        self.run_named_source("<input>", source)
+    }
+
+    /// Like `run_source`, with `name` (usually the script's path) shown in
+    /// error messages.
+    pub fn run_named_source(&mut self, name: &str, source: &str) -> Result<()> {
+        let (statements, mut errors) = self.parse(name, source, Parser::parse)?;
+
+        // what could be parsed is resolved too, so one run reports all errors
+        let mut resolver = Resolver::new(&mut self.interpreter);
+        errors.extend(resolver.resolve_all(&statements));
+        self.report_all(errors)?;
+
+        let result = self.interpreter.interpret(statements);
+        self.reported(result)
+    }
+
+    /// Evaluates a single expression such as `1 + 2` or `config.port` and
+    /// returns its value.
+    pub fn eval_expr(&mut self, source: &str) -> Result<Object> {
+        let (expr, mut errors) = self.parse("<expr>", source, Parser::parse_expression)?;
+
+        if let Some(expr) = &expr {
+            let mut resolver = Resolver::new(&mut self.interpreter);
+            errors.extend(resolver.resolve_expr(expr).err());
+        }
+        self.report_all(errors)?;
+        let expr = match expr {
+            Some(expr) => expr,
+            None => return Ok(Object::Literal(Literal::None)),
+        };
+
+        let result = self.interpreter.evaluate(&expr);
+        self.reported(result)
+    }
+
+    /// Redirects `print` statements, e.g. into an `output::Buffer`.
+    pub fn set_output<W: Write + 'static>(&mut self, output: W) {
+        self.interpreter.set_output(output)
+    }
+
+    /// Redirects error messages, which go to stderr by default.
+    pub fn set_diagnostics<W: Write + 'static>(&mut self, diagnostics: W) {
+        self.interpreter.set_diagnostics(diagnostics)
+    }
+
+    /// Chooses between source snippets (the default), one line per error and
+    /// JSON for tools.
+    pub fn set_error_format(&mut self, format: ErrorFormat) {
+        self.error_format = format
+    }
+
+    /// Enables ANSI colors in error messages, off by default.
+    pub fn set_color(&mut self, color: bool) {
+        self.color = color
+    }
+
+    /// The source of everything run so far, for rendering errors.
+    pub fn sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    /// Looks up a global variable, e.g. one set by a configuration script.
+    pub fn global(&self, name: &str) -> Option<Object> {
+        self.interpreter
+            .globals
+            .borrow()
+            .values
+            .borrow()
+            .get(name)
+            .cloned()
+    }
+
+    /// Makes the command line arguments of a script visible as `argc` and `arg(n)`.
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.interpreter.set_args(args)
+    }
+
+    /// Exposes a host function to scripts, e.g.
+    /// `lox.define_native("env", Arity::Fixed(1), |_, args| ...)`.
+    pub fn define_native<F>(&mut self, name: &str, arity: Arity, function: F)
+    where
+        F: Fn(&mut Interpreter, Vec<Object>) -> Result<Object> + 'static,
+    {
+        self.interpreter.define_native(name, arity, function)
+    }
+
+    /// Exposes a class implemented in Rust, see `LoxClass::native`.
+    pub fn define_class(&mut self, class: LoxClass) {
+        self.interpreter.define_class(class)
+    }
+
+    pub fn interpreter(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+
+    // Scans and parses `source`. Scan errors are reported right away; the
+    // syntax errors are returned with what could be parsed despite them.
+    fn parse<T>(
+        &mut self,
+        name: &str,
+        source: &str,
+        parse: impl FnOnce(&mut Parser) -> (T, Vec<Error>),
+    ) -> Result<(T, Vec<Error>)> {
+        let file = self.sources.add(name, source);
+        let mut scanner = Scanner::new(source.to_string()).with_file(file);
+        let (tokens, errors) = scanner.scan_tokens();
+        // skipped characters would only cause confusing syntax errors
+        self.report_all(errors)?;
+
+        Ok(parse(&mut Parser::new(tokens)))
+    }
+
+    // Reports every error in source order and returns the first one.
+    fn report_all(&mut self, mut errors: Vec<Error>) -> Result<()> {
+        errors.sort_by_key(|error| error.span().map(|span| span.start));
+        for error in &errors {
+            self.report(error);
+        }
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn reported<T>(&mut self, result: Result<T>) -> Result<T> {
+        if let Err(error) = &result {
+            self.report(error);
+        }
+        result
+    }
+
+    fn report(&mut self, error: &Error) {
+        let message = match self.error_format {
+            ErrorFormat::Human => Diagnostic::from_error(error).render(&self.sources, self.color),
+            ErrorFormat::Short => format!("{}\n", error),
+            ErrorFormat::Json => {
+                format!("{}\n", Diagnostic::from_error(error).to_json(&self.sources))
+            }
+        };
+        let _ = self.interpreter.diagnostics().write_all(message.as_bytes());
+    }
+}
@@ -1,15 +1,55 @@
-use crate::callable::LoxFunction;
-use crate::error::Result;
+use crate::callable::{LoxFunction, NativeFunction};
+use crate::error::{Error, Result};
 use crate::lox_instance::LoxInstance;
-use crate::{callable::LoxCallable, interpreter::Interpreter, object::Object};
+use crate::{
+    callable::{Arity, LoxCallable},
+    interpreter::Interpreter,
+    object::Object,
+};
+use std::any::Any;
 use std::collections::HashMap;
 use std::fmt;
+use std::rc::Rc;
+
+pub type NativeMethodFn = dyn Fn(&mut Interpreter, &LoxInstance, Vec<Object>) -> Result<Object>;
+pub type ConstructorFn = dyn Fn(&mut Interpreter, Vec<Object>) -> Result<Rc<dyn Any>>;
+// Returning `None` falls back to the instance's fields and methods.
+pub type GetterFn = dyn Fn(&LoxInstance, &str) -> Result<Option<Object>>;
+// Returning `false` stores the value as an ordinary field.
+pub type SetterFn = dyn Fn(&LoxInstance, &str, &Object) -> Result<bool>;
+
+#[derive(Clone)]
+pub struct NativeMethod {
+    arity: Arity,
+    function: Rc<NativeMethodFn>,
+}
+
+// Methods and property hooks of a class implemented by the host application.
+#[derive(Clone, Default)]
+struct HostHooks {
+    methods: HashMap<String, NativeMethod>,
+    constructor: Option<(Arity, Rc<ConstructorFn>)>,
+    getter: Option<Rc<GetterFn>>,
+    setter: Option<Rc<SetterFn>>,
+}
+
+impl fmt::Debug for HostHooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HostHooks")
+            .field("methods", &self.methods.keys().collect::<Vec<_>>())
+            .field("constructor", &self.constructor.is_some())
+            .field("getter", &self.getter.is_some())
+            .field("setter", &self.setter.is_some())
+            .finish()
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct LoxClass {
     pub name: String,
     super_class: Option<Box<LoxClass>>,
     methods: HashMap<String, LoxFunction>,
+    host: HostHooks,
 }
 
 impl LoxClass {
@@ -22,9 +62,56 @@ impl LoxClass {
             name,
             super_class,
             methods,
+            host: HostHooks::default(),
         }
     }
 
+    // Starts a class implemented in Rust. Register it with
+    // `Interpreter::define_class` once its methods and hooks are added.
+    pub fn native(name: &str) -> LoxClass {
+        LoxClass::new(name.to_string(), None, HashMap::new())
+    }
+
+    // Creates the opaque host data of each instance from the arguments the
+    // class is called with.
+    pub fn constructor<F>(mut self, arity: Arity, constructor: F) -> LoxClass
+    where
+        F: Fn(&mut Interpreter, Vec<Object>) -> Result<Rc<dyn Any>> + 'static,
+    {
+        self.host.constructor = Some((arity, Rc::new(constructor)));
+        self
+    }
+
+    pub fn method<F>(mut self, name: &str, arity: Arity, function: F) -> LoxClass
+    where
+        F: Fn(&mut Interpreter, &LoxInstance, Vec<Object>) -> Result<Object> + 'static,
+    {
+        self.host.methods.insert(
+            name.to_string(),
+            NativeMethod {
+                arity,
+                function: Rc::new(function),
+            },
+        );
+        self
+    }
+
+    pub fn getter<F>(mut self, getter: F) -> LoxClass
+    where
+        F: Fn(&LoxInstance, &str) -> Result<Option<Object>> + 'static,
+    {
+        self.host.getter = Some(Rc::new(getter));
+        self
+    }
+
+    pub fn setter<F>(mut self, setter: F) -> LoxClass
+    where
+        F: Fn(&LoxInstance, &str, &Object) -> Result<bool> + 'static,
+    {
+        self.host.setter = Some(Rc::new(setter));
+        self
+    }
+
     pub fn find_method(&self, name: String) -> Option<&LoxFunction> {
         if let Some(m) = self.methods.get(&name) {
             return Some(m);
@@ -37,24 +124,124 @@ impl LoxClass {
 
         None
     }
+
+    // Looks up a host method and binds it to `instance`.
+    pub fn find_native_method(&self, name: &str, instance: &LoxInstance) -> Option<NativeFunction> {
+        if let Some(method) = self.host.methods.get(name) {
+            let function = Rc::clone(&method.function);
+            let instance = instance.clone();
+            return Some(NativeFunction::new(
+                name,
+                method.arity,
+                move |interpreter, arguments| function(interpreter, &instance, arguments),
+            ));
+        }
+
+        match &self.super_class {
+            Some(super_class) => super_class.find_native_method(name, instance),
+            None => None,
+        }
+    }
+
+    // What `super.name` means in a method of a subclass of this class: a
+    // property of the getter hook, a method, a host method or, for `init`,
+    // the host constructor, bound to `instance` like `LoxInstance::get` does.
+    pub fn find_super_property(
+        &self,
+        name: &str,
+        instance: &LoxInstance,
+    ) -> Result<Option<Object>> {
+        if let Some(getter) = self.getter_hook() {
+            if let Some(o) = getter(instance, name)? {
+                return Ok(Some(o));
+            }
+        }
+
+        if let Some(method) = self.find_method(name.to_string()) {
+            return Ok(Some(Object::Func(method.bind(instance.clone()))));
+        }
+
+        if let Some(method) = self.find_native_method(name, instance) {
+            return Ok(Some(Object::Native(method)));
+        }
+
+        if let (Some((arity, constructor)), "init") = (self.find_constructor(), name) {
+            let constructor = Rc::clone(constructor);
+            let instance = instance.clone();
+            return Ok(Some(Object::Native(NativeFunction::new(
+                name,
+                *arity,
+                move |interpreter, arguments| {
+                    instance.init_data(constructor(interpreter, arguments)?)?;
+                    Ok(Object::Instance(instance.clone()))
+                },
+            ))));
+        }
+
+        Ok(None)
+    }
+
+    // Subclasses inherit the constructor. If they have an `init` method it
+    // only runs when that calls `super.init`, with the arguments given there.
+    fn find_constructor(&self) -> Option<&(Arity, Rc<ConstructorFn>)> {
+        match (&self.host.constructor, &self.super_class) {
+            (Some(constructor), _) => Some(constructor),
+            (None, Some(super_class)) => super_class.find_constructor(),
+            (None, None) => None,
+        }
+    }
+
+    pub fn getter_hook(&self) -> Option<Rc<GetterFn>> {
+        match (&self.host.getter, &self.super_class) {
+            (Some(getter), _) => Some(Rc::clone(getter)),
+            (None, Some(super_class)) => super_class.getter_hook(),
+            (None, None) => None,
+        }
+    }
+
+    pub fn setter_hook(&self) -> Option<Rc<SetterFn>> {
+        match (&self.host.setter, &self.super_class) {
+            (Some(setter), _) => Some(Rc::clone(setter)),
+            (None, Some(super_class)) => super_class.setter_hook(),
+            (None, None) => None,
+        }
+    }
 }
 
 impl LoxCallable for LoxClass {
     fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object> {
-        let instance = LoxInstance::new(self.clone());
         if let Some(initializer) = self.find_method("init".to_string()) {
+            let instance = LoxInstance::new(self.clone());
             initializer
                 .bind(instance.clone())
                 .call(interpreter, arguments)?;
+            return Ok(Object::Instance(instance));
         }
+
+        let instance = match self.find_constructor() {
+            Some((arity, _)) if !arity.accepts(arguments.len()) => {
+                return Err(Error::NativeError(format!(
+                    "Expected {} arguments but got {}.",
+                    arity,
+                    arguments.len()
+                )));
+            }
+            Some((_, constructor)) => {
+                let data = constructor(interpreter, arguments)?;
+                LoxInstance::with_data(self.clone(), data)
+            }
+            None => LoxInstance::new(self.clone()),
+        };
         Ok(Object::Instance(instance))
     }
 
-    fn arity(&self) -> usize {
+    fn arity(&self) -> Arity {
         if let Some(initializer) = self.find_method("init".to_string()) {
             initializer.arity()
+        } else if let Some((arity, _)) = self.find_constructor() {
+            *arity
         } else {
-            0
+            Arity::Fixed(0)
         }
     }
 }
@@ -1,4 +1,10 @@
-use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+use std::{
+    any::Any,
+    cell::{OnceCell, RefCell},
+    collections::HashMap,
+    fmt,
+    rc::Rc,
+};
 
 use crate::error::{Error, Result};
 use crate::{lox_class::LoxClass, object::Object, token::Token};
@@ -7,6 +13,9 @@ use crate::{lox_class::LoxClass, object::Object, token::Token};
 pub struct LoxInstance {
     class: Rc<LoxClass>,
     pub fields: Rc<RefCell<HashMap<String, Object>>>,
+    // opaque value owned by the host, only set for instances of native classes
+    // and of subclasses whose `init` called `super.init`
+    data: Rc<OnceCell<Rc<dyn Any>>>,
 }
 
 impl LoxInstance {
@@ -14,28 +23,76 @@ impl LoxInstance {
         LoxInstance {
             class: Rc::new(class),
             fields: Rc::new(RefCell::new(HashMap::new())),
+            data: Rc::new(OnceCell::new()),
         }
     }
 
+    pub fn with_data(class: LoxClass, data: Rc<dyn Any>) -> LoxInstance {
+        LoxInstance {
+            data: Rc::new(OnceCell::from(data)),
+            ..LoxInstance::new(class)
+        }
+    }
+
+    // Returns the host data if it was created as a `T`.
+    pub fn data<T: 'static>(&self) -> Option<&T> {
+        self.data.get()?.downcast_ref::<T>()
+    }
+
+    // Stores the host data of an instance created without it. Fails if the
+    // instance already has its data.
+    pub(crate) fn init_data(&self, data: Rc<dyn Any>) -> Result<()> {
+        self.data.set(data).map_err(|_| {
+            Error::NativeError(format!(
+                "{} instance was already initialized.",
+                self.class.name
+            ))
+        })
+    }
+
     pub fn get(&mut self, name: &Token) -> Result<Object> {
+        if let Some(getter) = self.class.getter_hook() {
+            if let Some(o) = getter(self, &name.lexeme).map_err(|e| e.at(name))? {
+                return Ok(o);
+            }
+        }
+
         if let Some(o) = self.fields.borrow().get(&name.lexeme) {
             return Ok(o.clone());
         }
 
-        if let Some(method) = self.class.find_method(name.lexeme.clone()) {
-            return Ok(Object::Func(method.bind(self.clone())));
+        if let Some(method) = self.method(&name.lexeme) {
+            return Ok(method);
         }
 
         Err(Error::RuntimeError(
-            name.clone(),
+            Box::new(name.clone()),
             format!("Undefined property '{}'", name.lexeme),
         ))
     }
 
-    pub fn set(&mut self, name: &Token, value: &Object) {
+    // A method of the class bound to the instance. Unlike `get` this ignores
+    // fields and the getter hook.
+    pub fn method(&self, name: &str) -> Option<Object> {
+        if let Some(method) = self.class.find_method(name.to_string()) {
+            return Some(Object::Func(method.bind(self.clone())));
+        }
+        self.class
+            .find_native_method(name, self)
+            .map(Object::Native)
+    }
+
+    pub fn set(&mut self, name: &Token, value: &Object) -> Result<()> {
+        if let Some(setter) = self.class.setter_hook() {
+            if setter(self, &name.lexeme, value).map_err(|e| e.at(name))? {
+                return Ok(());
+            }
+        }
+
         self.fields
             .borrow_mut()
             .insert(name.lexeme.clone(), value.clone());
+        Ok(())
     }
 }
 
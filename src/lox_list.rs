@@ -0,0 +1,274 @@
+use std::{cell::RefCell, cmp::Ordering, fmt, rc::Rc};
+
+use crate::{
+    arithmetic,
+    callable::{Arity, NativeFunction},
+    error::{Error, Result},
+    interpreter::Interpreter,
+    object::{self, Object},
+    token::{Literal, Token},
+};
+
+// A list value. Clones share the elements, so lists have reference semantics
+// like instances do.
+#[derive(Debug, Clone, Default)]
+pub struct LoxList {
+    elements: Rc<RefCell<Vec<Object>>>,
+}
+
+type ListMethod = fn(&mut Interpreter, &LoxList, Vec<Object>) -> Result<Object>;
+
+impl LoxList {
+    pub fn new(elements: Vec<Object>) -> LoxList {
+        LoxList {
+            elements: Rc::new(RefCell::new(elements)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.borrow().is_empty()
+    }
+
+    // A copy of the elements, so callbacks can modify the list while it's
+    // being walked.
+    pub fn elements(&self) -> Vec<Object> {
+        self.elements.borrow().clone()
+    }
+
+    // Whether both values are the same list.
+    pub fn ptr_eq(&self, other: &LoxList) -> bool {
+        Rc::ptr_eq(&self.elements, &other.elements)
+    }
+
+    pub fn index(&self, index: &Object) -> Result<Object> {
+        let position = self.position(index)?;
+        Ok(self.elements.borrow()[position].clone())
+    }
+
+    pub fn set_index(&self, index: &Object, value: Object) -> Result<()> {
+        let position = self.position(index)?;
+        self.elements.borrow_mut()[position] = value;
+        Ok(())
+    }
+
+    // `xs[start:end]` as a new list. Bounds may be negative or missing and
+    // are clamped to the list, as in Python.
+    pub fn slice(&self, start: Option<&Object>, end: Option<&Object>) -> Result<LoxList> {
+        let len = self.len();
+        let bound = |bound: Option<&Object>, default: usize| match bound {
+            None => Ok(default),
+            Some(Object::Literal(Literal::Isize(i))) if *i < 0 => {
+                Ok(len.saturating_sub(i.unsigned_abs()))
+            }
+            Some(Object::Literal(Literal::Isize(i))) => Ok((*i as usize).min(len)),
+            Some(Object::Literal(Literal::BigInt(i))) if i.is_negative() => Ok(0),
+            Some(Object::Literal(Literal::BigInt(_))) => Ok(len),
+            Some(_) => Err(Error::NativeError(String::from(
+                "Slice bounds must be integers.",
+            ))),
+        };
+        let start = bound(start, 0)?;
+        let end = bound(end, len)?.max(start);
+        Ok(LoxList::new(self.elements.borrow()[start..end].to_vec()))
+    }
+
+    // The position of `index` in the list, where negative indices count from
+    // the end.
+    fn position(&self, index: &Object) -> Result<usize> {
+        let len = self.len();
+        let position = match index {
+            Object::Literal(Literal::Isize(i)) if *i < 0 => len.checked_sub(i.unsigned_abs()),
+            Object::Literal(Literal::Isize(i)) => Some(*i as usize).filter(|i| *i < len),
+            Object::Literal(Literal::BigInt(_)) => None,
+            _ => {
+                return Err(Error::NativeError(String::from(
+                    "List index must be an integer.",
+                )))
+            }
+        };
+        position.ok_or_else(|| {
+            Error::NativeError(format!(
+                "Index {} is out of bounds for a list of length {}.",
+                index, len
+            ))
+        })
+    }
+
+    // Looks up a method and binds it to the list, e.g. `xs.push`.
+    pub fn get(&self, name: &Token) -> Result<Object> {
+        let (arity, method): (Arity, ListMethod) = match name.lexeme.as_str() {
+            "len" => (Arity::Fixed(0), |_, list, _| {
+                Ok(Object::Literal(Literal::Isize(list.len() as isize)))
+            }),
+            "push" => (Arity::Fixed(1), |_, list, arguments| {
+                list.elements.borrow_mut().extend(arguments);
+                Ok(Object::Literal(Literal::None))
+            }),
+            "pop" => (Arity::Fixed(0), |_, list, _| {
+                list.elements.borrow_mut().pop().ok_or_else(|| {
+                    Error::NativeError(String::from("Cannot pop from an empty list."))
+                })
+            }),
+            "map" => (Arity::Fixed(1), LoxList::map),
+            "filter" => (Arity::Fixed(1), LoxList::filter),
+            "sort" => (Arity::Variadic, LoxList::sort),
+            _ => {
+                return Err(Error::RuntimeError(
+                    Box::new(name.clone()),
+                    format!("Undefined property '{}'", name.lexeme),
+                ))
+            }
+        };
+        let list = self.clone();
+        Ok(Object::Native(NativeFunction::new(
+            &name.lexeme,
+            arity,
+            move |interpreter, arguments| method(interpreter, &list, arguments),
+        )))
+    }
+
+    fn map(
+        interpreter: &mut Interpreter,
+        list: &LoxList,
+        arguments: Vec<Object>,
+    ) -> Result<Object> {
+        let mut mapped = vec![];
+        for element in list.elements() {
+            mapped.push(interpreter.call_object(&arguments[0], vec![element])?);
+        }
+        Ok(Object::List(LoxList::new(mapped)))
+    }
+
+    fn filter(
+        interpreter: &mut Interpreter,
+        list: &LoxList,
+        arguments: Vec<Object>,
+    ) -> Result<Object> {
+        let mut kept = vec![];
+        for element in list.elements() {
+            let keep = interpreter.call_object(&arguments[0], vec![element.clone()])?;
+            if interpreter.is_truthy(keep) {
+                kept.push(element);
+            }
+        }
+        Ok(Object::List(LoxList::new(kept)))
+    }
+
+    // Sorts in place, stably. Without arguments numbers and strings are
+    // sorted in their natural order; `sort(compare)` orders `a` before `b`
+    // when `compare(a, b)` is negative.
+    fn sort(
+        interpreter: &mut Interpreter,
+        list: &LoxList,
+        arguments: Vec<Object>,
+    ) -> Result<Object> {
+        if arguments.len() > 1 {
+            return Err(Error::NativeError(format!(
+                "Expected 0 or 1 arguments but got {}.",
+                arguments.len()
+            )));
+        }
+        let mut compare = |a: &Object, b: &Object| match arguments.first() {
+            Some(compare) => {
+                let order = interpreter.call_object(compare, vec![a.clone(), b.clone()])?;
+                match order {
+                    Object::Literal(order) => {
+                        arithmetic::compare_literals(&order, &Literal::Isize(0))
+                            .ok_or_else(comparator_error)
+                    }
+                    _ => Err(comparator_error()),
+                }
+            }
+            None => match (a, b) {
+                (Object::Literal(a), Object::Literal(b)) => arithmetic::compare_literals(a, b),
+                _ => None,
+            }
+            .ok_or_else(|| {
+                Error::NativeError(String::from(
+                    "Only lists of numbers or of strings can be sorted without a comparator.",
+                ))
+            }),
+        };
+        let sorted = merge_sort(list.elements(), &mut compare)?;
+        *list.elements.borrow_mut() = sorted;
+        Ok(Object::Literal(Literal::None))
+    }
+}
+
+fn comparator_error() -> Error {
+    Error::NativeError(String::from("Comparator must return a number."))
+}
+
+// A stable sort that stops at the first error. Unlike `slice::sort_by` it
+// can't panic when a user's comparator isn't a total order.
+fn merge_sort<F>(mut elements: Vec<Object>, compare: &mut F) -> Result<Vec<Object>>
+where
+    F: FnMut(&Object, &Object) -> Result<Ordering>,
+{
+    if elements.len() <= 1 {
+        return Ok(elements);
+    }
+    let right = elements.split_off(elements.len() / 2);
+    let left = merge_sort(elements, compare)?;
+    let right = merge_sort(right, compare)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        if compare(r, l)? == Ordering::Less {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+impl fmt::Display for LoxList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let address = Rc::as_ptr(&self.elements) as *const ();
+        object::write_collection(f, address, "[...]", |f| {
+            write!(f, "[")?;
+            for (i, element) in self.elements.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", element.repr())?;
+            }
+            write!(f, "]")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(i: isize) -> Object {
+        Object::Literal(Literal::Isize(i))
+    }
+
+    #[test]
+    fn display_quotes_strings() {
+        let list = LoxList::new(vec![int(1), Object::Literal(Literal::String("a".into()))]);
+        assert_eq!(list.to_string(), "[1, \"a\"]");
+    }
+
+    #[test]
+    fn display_stops_at_cycles() {
+        let list = LoxList::new(vec![int(1)]);
+        list.elements.borrow_mut().push(Object::List(list.clone()));
+        assert_eq!(list.to_string(), "[1, [...]]");
+
+        // a list that appears twice without containing itself is written out
+        let outer = LoxList::new(vec![Object::List(list.clone()), Object::List(list)]);
+        assert_eq!(outer.to_string(), "[[1, [...]], [1, [...]]]");
+    }
+}
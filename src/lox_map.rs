@@ -0,0 +1,211 @@
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use crate::{
+    bigint::BigInt,
+    callable::{Arity, NativeFunction},
+    error::{Error, Result},
+    lox_list::LoxList,
+    object::{self, Object},
+    token::{Literal, Token},
+};
+
+// A map value with reference semantics, like lists. Keys are numbers,
+// strings, booleans or nil, and entries are kept in insertion order.
+#[derive(Debug, Clone, Default)]
+pub struct LoxMap {
+    entries: Rc<RefCell<Entries>>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    // where each key is in `entries`
+    positions: HashMap<Literal, usize>,
+    entries: Vec<(Literal, Object)>,
+}
+
+type MapMethod = fn(&LoxMap, Vec<Object>) -> Result<Object>;
+
+impl LoxMap {
+    pub fn len(&self) -> usize {
+        self.entries.borrow().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().entries.is_empty()
+    }
+
+    // Whether both values are the same map.
+    pub fn ptr_eq(&self, other: &LoxMap) -> bool {
+        Rc::ptr_eq(&self.entries, &other.entries)
+    }
+
+    // A copy of the entries in insertion order.
+    pub fn entries(&self) -> Vec<(Object, Object)> {
+        self.entries
+            .borrow()
+            .entries
+            .iter()
+            .map(|(key, value)| (Object::Literal(key.clone()), value.clone()))
+            .collect()
+    }
+
+    pub fn index(&self, key: &Object) -> Result<Object> {
+        let entries = self.entries.borrow();
+        match entries.positions.get(&map_key(key)?) {
+            Some(position) => Ok(entries.entries[*position].1.clone()),
+            None => Err(Error::NativeError(format!(
+                "Key {} is not in the map.",
+                key.repr()
+            ))),
+        }
+    }
+
+    // Adds the entry, or replaces the value of an existing key in place.
+    pub fn set_index(&self, key: &Object, value: Object) -> Result<()> {
+        let key = map_key(key)?;
+        let mut entries = self.entries.borrow_mut();
+        match entries.positions.get(&key) {
+            Some(position) => {
+                let position = *position;
+                entries.entries[position].1 = value;
+            }
+            None => {
+                let position = entries.entries.len();
+                entries.positions.insert(key.clone(), position);
+                entries.entries.push((key, value));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn has(&self, key: &Object) -> Result<bool> {
+        Ok(self.entries.borrow().positions.contains_key(&map_key(key)?))
+    }
+
+    pub fn remove(&self, key: &Object) -> Result<Option<Object>> {
+        let mut entries = self.entries.borrow_mut();
+        let position = match entries.positions.remove(&map_key(key)?) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+        let (_, value) = entries.entries.remove(position);
+        for later in entries.positions.values_mut() {
+            if *later > position {
+                *later -= 1;
+            }
+        }
+        Ok(Some(value))
+    }
+
+    // Looks up a method and binds it to the map, e.g. `m.keys`.
+    pub fn get(&self, name: &Token) -> Result<Object> {
+        let (arity, method): (Arity, MapMethod) = match name.lexeme.as_str() {
+            "len" => (Arity::Fixed(0), |map, _| {
+                Ok(Object::Literal(Literal::Isize(map.len() as isize)))
+            }),
+            "keys" => (Arity::Fixed(0), |map, _| {
+                let keys = map.entries().into_iter().map(|(key, _)| key).collect();
+                Ok(Object::List(LoxList::new(keys)))
+            }),
+            "values" => (Arity::Fixed(0), |map, _| {
+                let values = map.entries().into_iter().map(|(_, value)| value).collect();
+                Ok(Object::List(LoxList::new(values)))
+            }),
+            "has" => (Arity::Fixed(1), |map, arguments| {
+                Ok(Object::Literal(Literal::Bool(map.has(&arguments[0])?)))
+            }),
+            // the removed value, or nil if the key wasn't there
+            "remove" => (Arity::Fixed(1), |map, arguments| {
+                Ok(map
+                    .remove(&arguments[0])?
+                    .unwrap_or(Object::Literal(Literal::None)))
+            }),
+            _ => {
+                return Err(Error::RuntimeError(
+                    Box::new(name.clone()),
+                    format!("Undefined property '{}'", name.lexeme),
+                ))
+            }
+        };
+        let map = self.clone();
+        Ok(Object::Native(NativeFunction::new(
+            &name.lexeme,
+            arity,
+            move |_, arguments| method(&map, arguments),
+        )))
+    }
+}
+
+// The key under which `key` is stored. Whole floats are stored as integers,
+// bigints included, so `m[1]` and `m[1.0]` are the same entry, just as
+// `1 == 1.0`.
+fn map_key(key: &Object) -> Result<Literal> {
+    match key {
+        Object::Literal(Literal::Float(f)) if f.is_nan() => {
+            Err(Error::NativeError(String::from("Map keys cannot be NaN.")))
+        }
+        Object::Literal(Literal::Float(f)) => Ok(match BigInt::from_f64(*f) {
+            Some(i) => i.to_isize().map_or(Literal::BigInt(i), Literal::Isize),
+            None => Literal::Float(*f),
+        }),
+        Object::Literal(literal) => Ok(literal.clone()),
+        _ => Err(Error::NativeError(String::from(
+            "Map keys must be numbers, strings, booleans or nil.",
+        ))),
+    }
+}
+
+impl fmt::Display for LoxMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let address = Rc::as_ptr(&self.entries) as *const ();
+        object::write_collection(f, address, "{...}", |f| {
+            write!(f, "{{")?;
+            for (i, (key, value)) in self.entries.borrow().entries.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(
+                    f,
+                    "{}: {}",
+                    Object::Literal(key.clone()).repr(),
+                    value.repr()
+                )?;
+            }
+            write!(f, "}}")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Object {
+        Object::Literal(Literal::String(s.into()))
+    }
+
+    #[test]
+    fn display_keeps_insertion_order() {
+        let map = LoxMap::default();
+        map.set_index(&string("b"), string("x")).unwrap();
+        map.set_index(
+            &Object::Literal(Literal::Isize(1)),
+            Object::Literal(Literal::Bool(true)),
+        )
+        .unwrap();
+        assert_eq!(map.to_string(), "{\"b\": \"x\", 1: true}");
+    }
+
+    #[test]
+    fn display_stops_at_cycles() {
+        let map = LoxMap::default();
+        map.set_index(&string("x"), Object::Map(map.clone()))
+            .unwrap();
+        assert_eq!(map.to_string(), "{\"x\": {...}}");
+        assert_eq!(Object::Map(map.clone()).repr(), "{\"x\": {...}}");
+
+        let list = LoxList::new(vec![Object::Map(map.clone())]);
+        map.set_index(&string("l"), Object::List(list)).unwrap();
+        assert_eq!(map.to_string(), "{\"x\": {...}, \"l\": [{...}]}");
+    }
+}
@@ -1,97 +1,170 @@
-mod ast_printer;
-mod callable;
-mod environment;
-mod error;
-mod expr;
-mod interpreter;
-mod lox_class;
-mod lox_instance;
-mod object;
-mod parser;
-mod resolver;
-mod scanner;
-mod stmt;
-mod token;
-mod token_type;
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader},
+    env, fs,
+    io::{self, IsTerminal, Read, Write},
     process::exit,
 };
 
-use interpreter::Interpreter;
+use rust_lox::{diagnostic::ErrorFormat, error::Error, Lox};
 
-use crate::{
-    error::{Error, Result},
-    resolver::Resolver,
-};
+// exit codes from sysexits.h, as used by the reference implementation
+const EX_USAGE: i32 = 64;
+const EX_DATAERR: i32 = 65;
+const EX_NOINPUT: i32 = 66;
+const EX_SOFTWARE: i32 = 70;
 
-fn main() {
-    //run_prompt();
-    let path = String::from(r"C:\Users\oasis\OneDrive\Desktop\RUST\rust_lox\src\source.txt");
-    run_file(&path).unwrap();
+const USAGE: &str = "Usage: rust_lox [options] [script | -e <code> | -] [args...]";
+
+// How errors are shown, set by the options before the script.
+struct Options {
+    error_format: ErrorFormat,
+    color: bool,
 }
 
-fn run_file(path: &str) -> io::Result<()> {
-    let f = File::open(path)?;
-    let f = BufReader::new(f);
-    let mut source = String::from("");
-    let mut interpreter = interpreter::Interpreter::new();
+fn main() {
+    let mut args = env::args().skip(1).peekable();
 
-    for line in f.lines() {
-        source.push_str(&line.unwrap());
-        source.push_str("\n")
+    let mut options = Options {
+        error_format: ErrorFormat::Human,
+        color: io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none(),
+    };
+    while let Some(option) =
+        args.next_if(|arg| arg == "--color" || (arg.starts_with("--") && arg.contains('=')))
+    {
+        // a bare `--color` means `--color=always`
+        let (name, value) = option.split_once('=').unwrap_or((&option, "always"));
+        let valid = match (name, value) {
+            ("--color", "auto") => true,
+            ("--color", "always") => {
+                options.color = true;
+                true
+            }
+            ("--color", "never") => {
+                options.color = false;
+                true
+            }
+            ("--error-format", "human") => {
+                options.error_format = ErrorFormat::Human;
+                true
+            }
+            ("--error-format", "short") => {
+                options.error_format = ErrorFormat::Short;
+                true
+            }
+            ("--error-format", "json") => {
+                options.error_format = ErrorFormat::Json;
+                true
+            }
+            _ => false,
+        };
+        if !valid {
+            eprintln!("Invalid option '{}'.", option);
+            eprintln!("{}", USAGE);
+            exit(EX_USAGE);
+        }
     }
 
-    if let Err(e) = run(&source, &mut interpreter) {
-        eprintln!("{:?}", e);
-        exit(70);
+    let code = match args.next().as_deref() {
+        None => {
+            run_prompt(&options);
+            0
+        }
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            println!();
+            println!("  <script>     run the program in <script>");
+            println!("  -e <code>    run <code> given on the command line");
+            println!("  -            read the program from stdin");
+            println!();
+            println!("Options:");
+            println!("  --color[=auto|always|never]      colored error messages (auto: if stderr is a terminal)");
+            println!(
+                "  --error-format=human|short|json  source snippets, one line per error for logs, or JSON lines"
+            );
+            println!();
+            println!("Without arguments an interactive prompt is started.");
+            println!("Remaining arguments are passed to the program (see argc and arg(n)).");
+            0
+        }
+        Some("-e") => match args.next() {
+            Some(source) => run_source("<command line>", &source, args.collect(), &options),
+            None => {
+                eprintln!("{}", USAGE);
+                EX_USAGE
+            }
+        },
+        Some("-") => {
+            let mut source = String::new();
+            match io::stdin().read_to_string(&mut source) {
+                Ok(_) => run_source("<stdin>", &source, args.collect(), &options),
+                Err(e) => {
+                    eprintln!("Could not read stdin: {}", e);
+                    EX_NOINPUT
+                }
+            }
+        }
+        Some(flag) if flag.starts_with('-') => {
+            eprintln!("Unknown option '{}'.", flag);
+            eprintln!("{}", USAGE);
+            EX_USAGE
+        }
+        Some(path) => run_file(path, args.collect(), &options),
     };
 
-    Ok(())
+    exit(code);
 }
 
-fn run_prompt() {
-    let mut interpreter = interpreter::Interpreter::new();
-
-    loop {
-        print!("> ");
-        let mut line = String::new();
-        io::stdin().read_line(&mut line).unwrap();
-        if line.is_empty() {
-            break;
+fn run_file(path: &str, args: Vec<String>, options: &Options) -> i32 {
+    match fs::read_to_string(path) {
+        Ok(source) => run_source(path, &source, args, options),
+        Err(e) => {
+            eprintln!("Could not read '{}': {}", path, e);
+            EX_NOINPUT
         }
-        if let Err(e) = run(&line, &mut interpreter) {
-            eprintln!("{:?}", e);
-        };
     }
 }
 
-fn run(source: &str, interpreter: &mut Interpreter) -> Result<()> {
-    //let mut had_error = false;
-    let mut scanner = scanner::Scanner::new(source.to_string());
-    let tokens = scanner.scan_tokens();
-    for token in tokens.clone() {
-        println!("token:{}", token);
+fn run_source(name: &str, source: &str, args: Vec<String>, options: &Options) -> i32 {
+    let mut lox = session(options);
+    lox.set_args(args);
+
+    match lox.run_named_source(name, source) {
+        Ok(_) => 0,
+        Err(e) => exit_code(&e),
     }
-    let mut parser = parser::Parser::new(tokens);
-    let statements = match parser.parse() {
-        Ok(result) => result,
-        _ => return Err(Error::ParseError(String::from("parse error"))),
-    };
+}
 
-    //println!("statements:{:#?}", statements);
+fn run_prompt(options: &Options) {
+    let mut lox = session(options);
 
-    let mut resolver = Resolver::new(interpreter);
-    resolver.resolve_statements(&statements)?;
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        // errors are reported by `Lox` and don't end the session
+        let _ = lox.run_named_source("<prompt>", &line);
+    }
+}
 
-    let _ = match interpreter.interpret(statements.clone()) {
-        Ok(result) => result,
-        _ => return Err(Error::ParseError(String::from("interpreter error"))),
-    };
+fn session(options: &Options) -> Lox {
+    let mut lox = Lox::new();
+    lox.set_error_format(options.error_format);
+    lox.set_color(options.color);
+    lox
+}
 
-    // let mut ast_printer = ast_printer::AstPrinter {};
-    // let result = ast_printer.print_statements(statements.clone());
-    // println!("{}", result);
-    Ok(())
+// Errors have already been reported by `Lox`; this only picks the exit code.
+fn exit_code(error: &Error) -> i32 {
+    match error {
+        Error::ScanError(..) | Error::ParseError(..) | Error::ResolveError(..) => EX_DATAERR,
+        Error::RuntimeError(..)
+        | Error::NativeError(_)
+        | Error::Return(_)
+        | Error::Break(_)
+        | Error::Continue(_) => EX_SOFTWARE,
+        Error::WithNotes(error, _) => exit_code(error),
+    }
 }
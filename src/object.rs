@@ -1,14 +1,49 @@
-use std::fmt;
+use std::{cell::RefCell, collections::HashSet, fmt};
 
-use crate::{callable, lox_class, lox_instance, token};
+use crate::{callable, lox_class, lox_instance, lox_list, lox_map, token};
 
 #[derive(Debug, Clone)]
 pub enum Object {
     Literal(token::Literal),
     Func(callable::LoxFunction),
-    Clock(callable::Clock),
+    Native(callable::NativeFunction),
     Class(lox_class::LoxClass),
     Instance(lox_instance::LoxInstance),
+    List(lox_list::LoxList),
+    Map(lox_map::LoxMap),
+}
+
+impl Object {
+    // How the value is written inside a list or map, where strings are quoted
+    // and escaped so they read back as the same Lox literal.
+    pub fn repr(&self) -> String {
+        match self {
+            Object::Literal(token::Literal::String(s)) => quote(s),
+            object => object.to_string(),
+        }
+    }
+}
+
+// The escapes are the ones the scanner decodes. `$` is only escaped before a
+// `{`, where it would start an interpolation.
+fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\0' => quoted.push_str("\\0"),
+            '$' if chars.peek() == Some(&'{') => quoted.push_str("\\$"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
 }
 
 impl fmt::Display for Object {
@@ -16,9 +51,36 @@ impl fmt::Display for Object {
         match self {
             Object::Literal(l) => write!(f, "{}", l),
             Object::Func(l) => write!(f, "{}", l),
-            Object::Clock(l) => write!(f, "{}", l),
+            Object::Native(l) => write!(f, "{}", l),
             Object::Class(l) => write!(f, "{}", l),
             Object::Instance(l) => write!(f, "{}", l),
+            Object::List(l) => write!(f, "{}", l),
+            Object::Map(m) => write!(f, "{}", m),
         }
     }
 }
+
+thread_local! {
+    // the lists and maps currently being written, by address
+    static WRITING: RefCell<HashSet<*const ()>> = RefCell::new(HashSet::new());
+}
+
+// Writes a list or map with `write`, or just `placeholder` if it is already
+// being written further up, so one that contains itself prints as `[...]` or
+// `{...}` like in Python instead of recursing forever.
+pub(crate) fn write_collection<F>(
+    f: &mut fmt::Formatter,
+    address: *const (),
+    placeholder: &str,
+    write: F,
+) -> fmt::Result
+where
+    F: FnOnce(&mut fmt::Formatter) -> fmt::Result,
+{
+    if !WRITING.with(|writing| writing.borrow_mut().insert(address)) {
+        return write!(f, "{}", placeholder);
+    }
+    let result = write(f);
+    WRITING.with(|writing| writing.borrow_mut().remove(&address));
+    result
+}
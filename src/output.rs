@@ -0,0 +1,115 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{self, Write},
+    rc::Rc,
+};
+
+// A shared handle to wherever the interpreter writes program output or
+// diagnostics. Clones write to the same destination.
+#[derive(Clone)]
+pub struct Sink(Rc<RefCell<dyn Write>>);
+
+impl Sink {
+    pub fn new<W: Write + 'static>(writer: W) -> Sink {
+        Sink(Rc::new(RefCell::new(writer)))
+    }
+
+    pub fn stdout() -> Sink {
+        Sink::new(io::stdout())
+    }
+
+    pub fn stderr() -> Sink {
+        Sink::new(io::stderr())
+    }
+
+    // Calls `callback` with every chunk of text written to the sink.
+    pub fn from_fn<F: FnMut(&str) + 'static>(callback: F) -> Sink {
+        Sink::new(Callback(callback))
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+impl fmt::Debug for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sink")
+    }
+}
+
+struct Callback<F>(F);
+
+impl<F: FnMut(&str)> Write for Callback<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (self.0)(&String::from_utf8_lossy(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// An in-memory sink for capturing output, e.g. `Sink::new(buffer.clone())`.
+#[derive(Debug, Clone, Default)]
+pub struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Buffer {
+    pub fn new() -> Buffer {
+        Buffer::default()
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear()
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_write_to_the_same_buffer() {
+        let buffer = Buffer::new();
+        let mut sink = Sink::new(buffer.clone());
+        let mut copy = sink.clone();
+        write!(sink, "a").unwrap();
+        write!(copy, "b").unwrap();
+        assert_eq!(buffer.contents(), "ab");
+        buffer.clear();
+        assert_eq!(buffer.contents(), "");
+    }
+
+    #[test]
+    fn callbacks_receive_every_chunk() {
+        let chunks = Rc::new(RefCell::new(vec![]));
+        let received = Rc::clone(&chunks);
+        let mut sink = Sink::from_fn(move |chunk| received.borrow_mut().push(chunk.to_string()));
+        writeln!(sink, "one").unwrap();
+        sink.write_all("twö".as_bytes()).unwrap();
+        assert_eq!(chunks.borrow().concat(), "one\ntwö");
+    }
+}
@@ -1,62 +1,90 @@
 use crate::{
-    error,
+    error::{Error, Result},
     expr::Expr,
     stmt::Stmt,
     token::{Literal, Token},
     token_type::TokenType,
 };
 
-#[derive(Debug, Clone)]
-pub struct ParserError(String);
-
-type ParseResult<T> = Result<T, ParserError>;
+pub type ParseResult<T> = Result<T>;
 
 #[derive(Debug, Clone)]
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    errors: Vec<Error>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Parser {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            errors: Vec::new(),
+        }
     }
 
-    pub fn parse(&mut self) -> ParseResult<Vec<Stmt>> {
+    // Parses the whole program, recovering from syntax errors at statement
+    // boundaries. Statements with errors are left out of the returned tree.
+    pub fn parse(&mut self) -> (Vec<Stmt>, Vec<Error>) {
         let mut statements: Vec<Stmt> = Vec::new();
 
         while !self.is_at_end() {
-            statements.push(self.declaration()?);
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
         }
 
-        Ok(statements)
+        (statements, std::mem::take(&mut self.errors))
     }
 
-    fn declaration(&mut self) -> ParseResult<Stmt> {
+    // The expression is `None` only if there are errors.
+    pub fn parse_expression(&mut self) -> (Option<Expr>, Vec<Error>) {
+        let expr = match self.expression() {
+            Ok(expr) if !self.is_at_end() => {
+                let error = Parser::error(self.peek().clone(), "Expect end of expression.");
+                self.errors.push(error);
+                Some(expr)
+            }
+            Ok(expr) => Some(expr),
+            Err(e) => {
+                self.errors.push(e);
+                None
+            }
+        };
+        (expr, std::mem::take(&mut self.errors))
+    }
+
+    fn declaration(&mut self) -> Option<Stmt> {
+        let doc = self.peek().doc.clone();
         let result = if self.contains(&[TokenType::VAR]) {
             self.var_declaration()
         } else if self.contains(&[TokenType::CLASS]) {
-            self.class_declaration()
-        } else if self.contains(&[TokenType::FUN]) {
-            self.function(String::from("function"))
+            self.class_declaration(doc)
+        } else if self.check(TokenType::FUN) && !self.check_ahead(1, TokenType::LEFTPAREN) {
+            // `fun (...)` starts an expression statement with a lambda
+            self.advance();
+            self.function(String::from("function"), doc)
         } else {
             self.statement()
         };
 
         match result {
-            Ok(r) => Ok(r),
+            Ok(r) => Some(r),
             Err(e) => {
+                self.errors.push(e);
                 self.synchronize();
-                Err(e)
+                None
             }
         }
     }
 
-    fn class_declaration(&mut self) -> ParseResult<Stmt> {
+    fn class_declaration(&mut self, doc: Option<String>) -> ParseResult<Stmt> {
+        let keyword = self.previous().span;
         let name = self.consume(TokenType::IDENTIFIER, "Expect class name.")?;
 
         let super_class = if self.contains(&[TokenType::LESS]) {
-            self.consume(TokenType::IDENTIFIER, "Expect superclass name.");
+            self.consume(TokenType::IDENTIFIER, "Expect superclass name.")?;
             Some(Expr::Variable {
                 name: self.previous().clone(),
             })
@@ -68,21 +96,32 @@ impl Parser {
 
         let mut methods = vec![];
         while !self.check(TokenType::RIGHTBRACE) && !self.is_at_end() {
-            let function = self.function(String::from("method"))?;
+            let doc = self.peek().doc.clone();
+            let function = self.function(String::from("method"), doc)?;
             methods.push(function);
         }
-        self.consume(TokenType::RIGHTBRACE, "Expect '}' after class body.")?;
+        let brace = self.consume(TokenType::RIGHTBRACE, "Expect '}' after class body.")?;
 
         Ok(Stmt::Class {
             name,
             super_class,
             methods,
+            doc,
+            span: keyword.merge(brace.span),
         })
     }
 
     fn statement(&mut self) -> ParseResult<Stmt> {
+        if self.contains(&[TokenType::BREAK]) {
+            return self.break_statement();
+        }
+
+        if self.contains(&[TokenType::CONTINUE]) {
+            return self.continue_statement();
+        }
+
         if self.contains(&[TokenType::FOR]) {
-            return self.for_statement();
+            return self.for_statement(None);
         }
 
         if self.contains(&[TokenType::IF]) {
@@ -98,21 +137,78 @@ impl Parser {
         }
 
         if self.contains(&[TokenType::WHILE]) {
-            return self.while_statement();
+            return self.while_statement(None);
+        }
+
+        if self.check(TokenType::IDENTIFIER) && self.check_ahead(1, TokenType::COLON) {
+            return self.labeled_statement();
         }
 
         if self.contains(&[TokenType::LEFTBRACE]) {
+            let brace = self.previous().span;
+            let statements = self.block()?;
             return Ok(Stmt::Block {
-                statements: self.block()?,
+                statements,
+                span: brace.merge(self.previous().span),
             });
         }
 
         self.expression_statement()
     }
 
-    fn for_statement(&mut self) -> ParseResult<Stmt> {
+    // `outer: while (...) { ... break outer; }`
+    fn labeled_statement(&mut self) -> ParseResult<Stmt> {
+        let label = self.advance().clone();
+        self.advance();
+        if self.contains(&[TokenType::WHILE]) {
+            self.while_statement(Some(label))
+        } else if self.contains(&[TokenType::FOR]) {
+            self.for_statement(Some(label))
+        } else {
+            Err(Parser::error(
+                self.peek().clone(),
+                "Expect 'while' or 'for' after label.",
+            ))
+        }
+    }
+
+    fn break_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
+        let label = self.jump_label(&keyword)?;
+        Ok(Stmt::Break { keyword, label })
+    }
+
+    fn continue_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
+        let label = self.jump_label(&keyword)?;
+        Ok(Stmt::Continue { keyword, label })
+    }
+
+    // The optional label after `break` or `continue`, and the `;`.
+    fn jump_label(&mut self, keyword: &Token) -> ParseResult<Option<Token>> {
+        let label = if self.contains(&[TokenType::IDENTIFIER]) {
+            Some(self.previous().clone())
+        } else {
+            None
+        };
+        self.consume(
+            TokenType::SEMICOLON,
+            &format!("Expect ';' after '{}'.", keyword.lexeme),
+        )?;
+        Ok(label)
+    }
+
+    fn for_statement(&mut self, label: Option<Token>) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
         self.consume(TokenType::LEFTPAREN, "Expect '(' after 'for'.")?;
 
+        if self.check(TokenType::VAR)
+            && self.check_ahead(1, TokenType::IDENTIFIER)
+            && self.check_ahead(2, TokenType::IN)
+        {
+            return self.for_in_statement(label);
+        }
+
         let initializer = if self.contains(&[TokenType::SEMICOLON]) {
             None
         } else if self.contains(&[TokenType::VAR]) {
@@ -126,41 +222,55 @@ impl Parser {
         } else {
             Expr::Literal {
                 value: Literal::Bool(true),
+                span: self.peek().span,
             }
         };
         self.consume(TokenType::SEMICOLON, "Expect ';' after loop condition.")?;
 
         let increment = if !self.check(TokenType::RIGHTPAREN) {
-            Some(self.expression()?)
+            Some(Box::new(self.expression()?))
         } else {
             None
         };
         self.consume(TokenType::RIGHTPAREN, "Expect ')' after for clauses.")?;
 
-        let mut body = self.statement()?;
-        if increment.is_some() {
-            body = Stmt::Block {
-                statements: vec![
-                    body,
-                    Stmt::Expression {
-                        expression: increment.unwrap(),
-                    },
-                ],
-            }
-        }
-        body = Stmt::While {
+        let body = self.statement()?;
+        let mut body = Stmt::While {
+            keyword,
             condition,
             body: Box::new(body),
+            increment,
+            label,
         };
-        if initializer.is_some() {
+        if let Some(initializer) = initializer {
+            let span = body.span();
             body = Stmt::Block {
-                statements: vec![initializer.unwrap(), body],
+                statements: vec![initializer, body],
+                span,
             }
         }
         Ok(body)
     }
 
+    // `for (var x in iterable) body`, after the `(`.
+    fn for_in_statement(&mut self, label: Option<Token>) -> ParseResult<Stmt> {
+        self.advance();
+        let name = self.advance().clone();
+        let keyword = self.advance().clone();
+        let iterable = Box::new(self.expression()?);
+        self.consume(TokenType::RIGHTPAREN, "Expect ')' after iterable.")?;
+        let body = Box::new(self.statement()?);
+        Ok(Stmt::ForIn {
+            name,
+            keyword,
+            iterable,
+            body,
+            label,
+        })
+    }
+
     fn if_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
         self.consume(TokenType::LEFTPAREN, "Expect '(' after 'if'.")?;
         let condition = self.expression()?;
         self.consume(TokenType::RIGHTPAREN, "Expect ')' after if condition.")?;
@@ -172,6 +282,7 @@ impl Parser {
             None
         };
         Ok(Stmt::If {
+            keyword,
             condition,
             then_branch,
             else_branch,
@@ -179,9 +290,13 @@ impl Parser {
     }
 
     fn print_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
         let value = self.expression()?;
         self.consume(TokenType::SEMICOLON, "Expect ';' after value.")?;
-        Ok(Stmt::Print { expression: value })
+        Ok(Stmt::Print {
+            keyword,
+            expression: value,
+        })
     }
 
     fn return_statemet(&mut self) -> ParseResult<Stmt> {
@@ -191,34 +306,65 @@ impl Parser {
         } else {
             Expr::Literal {
                 value: Literal::None,
+                span: keyword.span,
             }
         };
         self.consume(TokenType::SEMICOLON, "Expect ';' after return value.")?;
         Ok(Stmt::Return { keyword, value })
     }
 
-    fn while_statement(&mut self) -> ParseResult<Stmt> {
+    fn while_statement(&mut self, label: Option<Token>) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
         self.consume(TokenType::LEFTPAREN, "Expectct '(' after 'while'.")?;
         let condition = self.expression()?;
         self.consume(TokenType::RIGHTPAREN, "Expectct ')' after condition.")?;
         let body = Box::new(self.statement()?);
-        Ok(Stmt::While { condition, body })
+        Ok(Stmt::While {
+            keyword,
+            condition,
+            body,
+            increment: None,
+            label,
+        })
     }
 
-    fn function(&mut self, kind: String) -> ParseResult<Stmt> {
+    fn function(&mut self, kind: String, doc: Option<String>) -> ParseResult<Stmt> {
+        // methods have no `fun`, so they start at their name
+        let start = if self.previous().token_type == TokenType::FUN {
+            self.previous().span
+        } else {
+            self.peek().span
+        };
         let name = self.consume(TokenType::IDENTIFIER, &format!("Expect {} name.", kind))?;
         self.consume(
             TokenType::LEFTPAREN,
             &format!("Expect '(' after {} name.", kind),
         )?;
+        let parameters = self.parameters()?;
+
+        self.consume(
+            TokenType::LEFTBRACE,
+            &format!("Expect '{{' before {} body.", kind),
+        )?;
+        let body = self.block()?;
+        Ok(Stmt::Function {
+            name,
+            params: parameters,
+            body,
+            doc,
+            span: start.merge(self.previous().span),
+        })
+    }
+
+    // The parameter list after the `(`, and the `)`.
+    fn parameters(&mut self) -> ParseResult<Vec<Token>> {
         let mut parameters = vec![];
         loop {
             if !self.check(TokenType::RIGHTPAREN) {
-                if parameters.len() >= 255 {
-                    return Err(Parser::error(
-                        self.peek().clone(),
-                        "Cannot have more than 255 parameters.",
-                    ));
+                if parameters.len() == 255 {
+                    let error =
+                        Parser::error(self.peek().clone(), "Cannot have more than 255 parameters.");
+                    self.errors.push(error);
                 }
                 parameters.push(self.consume(TokenType::IDENTIFIER, "Expect parameter name.")?)
             }
@@ -227,19 +373,60 @@ impl Parser {
             }
         }
         self.consume(TokenType::RIGHTPAREN, "Expect ')' after parameters.")?;
+        Ok(parameters)
+    }
 
-        self.consume(
-            TokenType::LEFTBRACE,
-            &format!("Expect '{{' before {} body.", kind),
-        )?;
+    // `fun (a, b) { ... }`, after the `fun`.
+    fn lambda(&mut self) -> ParseResult<Expr> {
+        let keyword = self.previous().clone();
+        self.consume(TokenType::LEFTPAREN, "Expect '(' after 'fun'.")?;
+        let params = self.parameters()?;
+        self.consume(TokenType::LEFTBRACE, "Expect '{' before lambda body.")?;
         let body = self.block()?;
-        Ok(Stmt::Function {
-            name,
-            params: parameters,
+        Ok(Expr::Lambda {
+            span: keyword.span.merge(self.previous().span),
+            keyword,
+            params,
             body,
         })
     }
 
+    // `(a, b) => a + b`, after the `(`.
+    fn arrow_function(&mut self) -> ParseResult<Expr> {
+        let paren = self.previous().span;
+        let params = self.parameters()?;
+        let keyword = self.consume(TokenType::ARROW, "Expect '=>' after parameters.")?;
+        let value = self.expression()?;
+        Ok(Expr::Lambda {
+            keyword: keyword.clone(),
+            params,
+            span: paren.merge(value.span()),
+            body: vec![Stmt::Return { keyword, value }],
+        })
+    }
+
+    // Whether the current `(` starts the parameters of an arrow function
+    // rather than a grouping, which takes looking ahead to the `=>`.
+    fn is_arrow_function(&self) -> bool {
+        let mut tokens = self.tokens[self.current + 1..]
+            .iter()
+            .map(|token| token.token_type);
+        let mut next = tokens.next();
+        if next != Some(TokenType::RIGHTPAREN) {
+            loop {
+                if next != Some(TokenType::IDENTIFIER) {
+                    return false;
+                }
+                match tokens.next() {
+                    Some(TokenType::COMMA) => next = tokens.next(),
+                    Some(TokenType::RIGHTPAREN) => break,
+                    _ => return false,
+                }
+            }
+        }
+        tokens.next() == Some(TokenType::ARROW)
+    }
+
     fn expression_statement(&mut self) -> ParseResult<Stmt> {
         let expr = self.expression()?;
         self.consume(TokenType::SEMICOLON, "Expect ':' after value.")?;
@@ -247,21 +434,15 @@ impl Parser {
     }
 
     fn expression(&mut self) -> ParseResult<Expr> {
-        let result = self.assignment();
-
-        match result {
-            Ok(r) => Ok(r),
-            Err(e) => {
-                self.synchronize();
-                Err(e)
-            }
-        }
+        self.assignment()
     }
 
     fn block(&mut self) -> ParseResult<Vec<Stmt>> {
         let mut statements: Vec<Stmt> = vec![];
         while !self.check(TokenType::RIGHTBRACE) && !self.is_at_end() {
-            statements.push(self.declaration()?)
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
         }
         self.consume(TokenType::RIGHTBRACE, "Expect '}' after block.")?;
         Ok(statements)
@@ -284,12 +465,79 @@ impl Parser {
                     name,
                     value: Box::new(value),
                 }),
-                _ => Err(Parser::error(equals, "Invalid assignment target.")),
+                Expr::Index {
+                    object,
+                    bracket,
+                    index,
+                } => Ok(Expr::SetIndex {
+                    object,
+                    bracket,
+                    index,
+                    value: Box::new(value),
+                }),
+                // the parser isn't confused, so there's no need to synchronize
+                _ => {
+                    self.errors
+                        .push(Parser::error(equals, "Invalid assignment target."));
+                    Ok(expr)
+                }
             };
         }
+
+        if self.contains(&[
+            TokenType::PLUSEQUAL,
+            TokenType::MINUSEQUAL,
+            TokenType::STAREQUAL,
+            TokenType::SLASHEQUAL,
+        ]) {
+            let operator = self.previous().clone();
+            let value = self.assignment()?;
+            return Ok(self.compound_assignment(expr, operator, value, false));
+        }
         Ok(expr)
     }
 
+    fn compound_assignment(
+        &mut self,
+        target: Expr,
+        operator: Token,
+        value: Expr,
+        postfix: bool,
+    ) -> Expr {
+        match target {
+            Expr::Variable { name } => Expr::CompoundAssign {
+                name,
+                operator,
+                value: Box::new(value),
+                postfix,
+            },
+            Expr::Get { object, name } => Expr::CompoundSet {
+                object,
+                name,
+                operator,
+                value: Box::new(value),
+                postfix,
+            },
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => Expr::CompoundSetIndex {
+                object,
+                bracket,
+                index,
+                operator,
+                value: Box::new(value),
+                postfix,
+            },
+            _ => {
+                self.errors
+                    .push(Parser::error(operator, "Invalid assignment target."));
+                target
+            }
+        }
+    }
+
     fn or(&mut self) -> ParseResult<Expr> {
         let mut expr = self.and()?;
         while self.contains(&[TokenType::OR]) {
@@ -319,6 +567,7 @@ impl Parser {
     }
 
     fn var_declaration(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().span;
         let name = self.consume(TokenType::IDENTIFIER, "Expect variable name.")?;
 
         let initializer = if self.contains(&[TokenType::EQUAL]) {
@@ -326,14 +575,19 @@ impl Parser {
         } else {
             Expr::Literal {
                 value: Literal::None,
+                span: name.span,
             }
         };
 
-        self.consume(
+        let semicolon = self.consume(
             TokenType::SEMICOLON,
             "Expect ';' after variable declaration.",
         )?;
-        Ok(Stmt::Var { name, initializer })
+        Ok(Stmt::Var {
+            name,
+            initializer,
+            span: keyword.merge(semicolon.span),
+        })
     }
 
     fn equality(&mut self) -> ParseResult<Expr> {
@@ -351,10 +605,7 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> ParseResult<Expr> {
-        let mut expr = match self.term() {
-            Ok(result) => result,
-            Err(err) => return Err(err),
-        };
+        let mut expr = self.bit_or()?;
 
         while self.contains(&[
             TokenType::GREATER,
@@ -363,10 +614,47 @@ impl Parser {
             TokenType::LESSEQUAL,
         ]) {
             let operator = self.previous().clone();
-            let right = match self.term() {
-                Ok(result) => result,
-                Err(err) => return Err(err),
-            };
+            let right = self.bit_or()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            }
+        }
+        Ok(expr)
+    }
+
+    // The bitwise operators bind tighter than comparisons, unlike in C, so
+    // `a & 1 == 0` means `(a & 1) == 0`.
+    fn bit_or(&mut self) -> ParseResult<Expr> {
+        self.left_associative(&[TokenType::PIPE], Parser::bit_xor)
+    }
+
+    fn bit_xor(&mut self) -> ParseResult<Expr> {
+        self.left_associative(&[TokenType::CARET], Parser::bit_and)
+    }
+
+    fn bit_and(&mut self) -> ParseResult<Expr> {
+        self.left_associative(&[TokenType::AMPERSAND], Parser::shift)
+    }
+
+    fn shift(&mut self) -> ParseResult<Expr> {
+        self.left_associative(
+            &[TokenType::LESSLESS, TokenType::GREATERGREATER],
+            Parser::term,
+        )
+    }
+
+    // operand (operator operand)*
+    fn left_associative(
+        &mut self,
+        operators: &[TokenType],
+        operand: fn(&mut Parser) -> ParseResult<Expr>,
+    ) -> ParseResult<Expr> {
+        let mut expr = operand(self)?;
+        while self.contains(operators) {
+            let operator = self.previous().clone();
+            let right = operand(self)?;
             expr = Expr::Binary {
                 left: Box::new(expr),
                 operator,
@@ -378,17 +666,11 @@ impl Parser {
 
     //addition
     fn term(&mut self) -> ParseResult<Expr> {
-        let mut expr = match self.factor() {
-            Ok(multiplication) => multiplication,
-            Err(err) => return Err(err),
-        };
+        let mut expr = self.factor()?;
 
         while self.contains(&[TokenType::MINUS, TokenType::PLUS]) {
             let operator = self.previous().clone();
-            let right = match self.factor() {
-                Ok(multiplication) => multiplication,
-                Err(err) => return Err(err),
-            };
+            let right = self.factor()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
                 operator,
@@ -400,17 +682,16 @@ impl Parser {
 
     //multiplication
     fn factor(&mut self) -> ParseResult<Expr> {
-        let mut expr = match self.unary() {
-            Ok(unary) => unary,
-            Err(err) => return Err(err),
-        };
+        let mut expr = self.unary()?;
 
-        while self.contains(&[TokenType::SLASH, TokenType::STAR]) {
+        while self.contains(&[
+            TokenType::SLASH,
+            TokenType::STAR,
+            TokenType::PERCENT,
+            TokenType::TILDESLASH,
+        ]) {
             let operator = self.previous().clone();
-            let right = match self.unary() {
-                Ok(unary) => unary,
-                Err(err) => return Err(err),
-            };
+            let right = self.unary()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
                 operator,
@@ -421,27 +702,95 @@ impl Parser {
     }
 
     fn unary(&mut self) -> ParseResult<Expr> {
-        if self.contains(&[TokenType::BANG, TokenType::MINUS]) {
+        if self.contains(&[TokenType::BANG, TokenType::MINUS, TokenType::TILDE]) {
             let operator = self.previous().clone();
-            let right = match self.unary() {
-                Ok(unary) => unary,
-                Err(err) => return Err(err),
-            };
+            let right = self.unary()?;
             return Ok(Expr::Unary {
                 operator,
                 right: Box::new(right),
             });
         }
 
-        return self.call();
+        self.power()
+    }
+
+    // `**` is right-associative and binds tighter than a unary operator on
+    // its left: `-2 ** 2` is `-(2 ** 2)` and `2 ** 3 ** 2` is `2 ** 9`.
+    fn power(&mut self) -> ParseResult<Expr> {
+        let expr = self.increment()?;
+        if self.contains(&[TokenType::STARSTAR]) {
+            let operator = self.previous().clone();
+            let right = self.unary()?;
+            return Ok(Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
+    // `++a` and `a--`, which add or subtract 1 like `a += 1` does
+    fn increment(&mut self) -> ParseResult<Expr> {
+        let (expr, operator, postfix) =
+            if self.contains(&[TokenType::PLUSPLUS, TokenType::MINUSMINUS]) {
+                let operator = self.previous().clone();
+                (self.call()?, operator, false)
+            } else {
+                let expr = self.call()?;
+                if !self.contains(&[TokenType::PLUSPLUS, TokenType::MINUSMINUS]) {
+                    return Ok(expr);
+                }
+                (expr, self.previous().clone(), true)
+            };
+        let one = Expr::Literal {
+            value: Literal::Isize(1),
+            span: operator.span,
+        };
+        Ok(self.compound_assignment(expr, operator, one, postfix))
+    }
+
+    // `xs[i]` or `xs[start:end]`, after the `[`.
+    fn finish_index(&mut self, object: Expr) -> ParseResult<Expr> {
+        let object = Box::new(object);
+        let start = if self.check(TokenType::COLON) {
+            None
+        } else {
+            let index = Box::new(self.expression()?);
+            if !self.check(TokenType::COLON) {
+                let bracket = self.consume(TokenType::RIGHTBRACKET, "Expect ']' after index.")?;
+                return Ok(Expr::Index {
+                    object,
+                    bracket,
+                    index,
+                });
+            }
+            Some(index)
+        };
+        // the `:` of a slice
+        self.advance();
+        let end = if self.check(TokenType::RIGHTBRACKET) {
+            None
+        } else {
+            Some(Box::new(self.expression()?))
+        };
+        let bracket = self.consume(TokenType::RIGHTBRACKET, "Expect ']' after slice.")?;
+        Ok(Expr::Slice {
+            object,
+            bracket,
+            start,
+            end,
+        })
     }
 
     fn finish_call(&mut self, callee: Expr) -> ParseResult<Expr> {
         let mut arguments = vec![];
         if !self.check(TokenType::RIGHTPAREN) {
             loop {
-                if arguments.len() >= 255 {
-                    Parser::error(self.peek().clone(), "Cannot have more than 255 arguments.");
+                if arguments.len() == 255 {
+                    let error =
+                        Parser::error(self.peek().clone(), "Cannot have more than 255 arguments.");
+                    self.errors.push(error);
                 }
                 arguments.push(self.expression()?);
                 if !self.contains(&[TokenType::COMMA]) {
@@ -470,6 +819,8 @@ impl Parser {
                     object: Box::new(expr),
                     name,
                 }
+            } else if self.contains(&[TokenType::LEFTBRACKET]) {
+                expr = self.finish_index(expr)?;
             } else {
                 break;
             }
@@ -481,27 +832,35 @@ impl Parser {
         if self.contains(&[TokenType::FALSE]) {
             return Ok(Expr::Literal {
                 value: Literal::Bool(false),
+                span: self.previous().span,
             });
         };
 
         if self.contains(&[TokenType::TRUE]) {
             return Ok(Expr::Literal {
                 value: Literal::Bool(true),
+                span: self.previous().span,
             });
         }
 
         if self.contains(&[TokenType::NIL]) {
             return Ok(Expr::Literal {
                 value: Literal::None,
+                span: self.previous().span,
             });
         }
 
         if self.contains(&[TokenType::NUMBER, TokenType::STRING]) {
             return Ok(Expr::Literal {
                 value: self.previous().literal.clone(),
+                span: self.previous().span,
             });
         }
 
+        if self.contains(&[TokenType::INTERPOLATION]) {
+            return self.interpolation();
+        }
+
         if self.contains(&[TokenType::SUPER]) {
             let keyword = self.previous().clone();
             self.consume(TokenType::DOT, "Expect '.' after 'super'.")?;
@@ -521,17 +880,85 @@ impl Parser {
             });
         }
 
+        if self.contains(&[TokenType::FUN]) {
+            return self.lambda();
+        }
+
+        if self.contains(&[TokenType::LEFTBRACE]) {
+            let mut entries = vec![];
+            while !self.check(TokenType::RIGHTBRACE) {
+                let key = self.expression()?;
+                self.consume(TokenType::COLON, "Expect ':' after map key.")?;
+                let value = self.expression()?;
+                entries.push((key, value));
+                if !self.contains(&[TokenType::COMMA]) {
+                    break;
+                }
+            }
+            let brace = self.consume(TokenType::RIGHTBRACE, "Expect '}' after map entries.")?;
+            return Ok(Expr::Map { entries, brace });
+        }
+
+        if self.contains(&[TokenType::LEFTBRACKET]) {
+            let left = self.previous().span;
+            let mut elements = vec![];
+            while !self.check(TokenType::RIGHTBRACKET) {
+                elements.push(self.expression()?);
+                if !self.contains(&[TokenType::COMMA]) {
+                    break;
+                }
+            }
+            let right = self.consume(TokenType::RIGHTBRACKET, "Expect ']' after list elements.")?;
+            return Ok(Expr::List {
+                elements,
+                span: left.merge(right.span),
+            });
+        }
+
+        if self.check(TokenType::LEFTPAREN) && self.is_arrow_function() {
+            self.advance();
+            return self.arrow_function();
+        }
+
         if self.contains(&[TokenType::LEFTPAREN]) {
+            let left = self.previous().span;
             let expr = self.expression()?;
-            self.consume(TokenType::RIGHTPAREN, "Expect')' after expression.")?;
+            let right = self.consume(TokenType::RIGHTPAREN, "Expect')' after expression.")?;
             return Ok(Expr::Grouping {
                 expression: Box::new(expr),
+                span: left.merge(right.span),
             });
         }
 
         Err(Parser::error(self.peek().clone(), "Expect expression."))
     }
 
+    // The scanner splits `"a ${b} c ${d}"` into INTERPOLATION("a "), b,
+    // INTERPOLATION(" c "), d and INTERPOLATIONEND("").
+    fn interpolation(&mut self) -> ParseResult<Expr> {
+        let mut parts = vec![];
+        loop {
+            let segment = self.previous().clone();
+            parts.push(Expr::Literal {
+                value: segment.literal,
+                span: segment.span,
+            });
+            parts.push(self.expression()?);
+            if !self.contains(&[TokenType::INTERPOLATION]) {
+                break;
+            }
+        }
+        let end = self.consume(
+            TokenType::INTERPOLATIONEND,
+            "Expect '}' after interpolated expression.",
+        )?;
+        parts.push(Expr::Literal {
+            value: end.literal,
+            span: end.span,
+        });
+        Ok(Expr::Interpolation { parts })
+    }
+
     fn consume(&mut self, token_type: TokenType, message: &str) -> ParseResult<Token> {
         if self.check(token_type) {
             return Ok(self.advance().clone());
@@ -541,9 +968,8 @@ impl Parser {
         Err(Parser::error(token.clone(), message))
     }
 
-    fn error(token: Token, message: &str) -> ParserError {
-        error::parser_error(token, &message);
-        ParserError(String::from(message))
+    fn error(token: Token, message: &str) -> Error {
+        Error::ParseError(Box::new(token), String::from(message))
     }
 
     fn synchronize(&mut self) {
@@ -587,6 +1013,13 @@ impl Parser {
         self.peek().token_type == token_type
     }
 
+    // Like `check`, for the token `distance` tokens after the current one.
+    fn check_ahead(&self, distance: usize, token_type: TokenType) -> bool {
+        self.tokens
+            .get(self.current + distance)
+            .is_some_and(|token| token.token_type == token_type)
+    }
+
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1
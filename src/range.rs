@@ -0,0 +1,60 @@
+use std::rc::Rc;
+
+use crate::{callable::Arity, error::Error, lox_class::LoxClass, object::Object, token::Literal};
+
+// `Range(end)`, `Range(start, end)` and `Range(start, end, step)`: the
+// integers from `start`, 0 by default, up to but excluding `end`. A global
+// native class whose instances `for (var i in ...)` can walk without
+// allocating a list.
+#[derive(Debug, Clone, Copy)]
+pub struct Range {
+    start: isize,
+    end: isize,
+    step: isize,
+}
+
+impl Range {
+    pub fn class() -> LoxClass {
+        LoxClass::native("Range").constructor(Arity::Variadic, |_, arguments| {
+            let mut bounds = vec![];
+            for argument in &arguments {
+                match argument {
+                    Object::Literal(Literal::Isize(i)) => bounds.push(*i),
+                    _ => {
+                        return Err(Error::NativeError(String::from(
+                            "Range bounds must be integers.",
+                        )))
+                    }
+                }
+            }
+            let (start, end, step) = match bounds[..] {
+                [end] => (0, end, 1),
+                [start, end] => (start, end, 1),
+                [start, end, step] => (start, end, step),
+                _ => {
+                    return Err(Error::NativeError(format!(
+                        "Expected 1 to 3 arguments but got {}.",
+                        bounds.len()
+                    )))
+                }
+            };
+            if step == 0 {
+                return Err(Error::NativeError(String::from(
+                    "Range step cannot be zero.",
+                )));
+            }
+            Ok(Rc::new(Range { start, end, step }))
+        })
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = isize> {
+        let Range { start, end, step } = self;
+        std::iter::successors(Some(start), move |i| i.checked_add(step)).take_while(move |i| {
+            if step > 0 {
+                *i < end
+            } else {
+                *i > end
+            }
+        })
+    }
+}
@@ -2,29 +2,34 @@ use std::collections::HashMap;
 
 use crate::{
     callable::FunctionType,
-    error::{resolve_error, Error, Result},
+    diagnostic::Note,
+    error::{Error, Result},
     expr::{Acceptor as ExprAcceptor, Expr, Visitor as ExprVisitor},
     interpreter::Interpreter,
     lox_class::ClassType,
     stmt::{Acceptor as StmtAcceptor, Stmt, Visitor as StmtVisitor},
-    token::{Literal, Token},
+    token::{Literal, Span, Token},
 };
 
 #[derive(Debug)]
 pub struct Resolver<'a> {
     interpreter: &'a mut Interpreter,
-    pub scopes: Vec<HashMap<String, bool>>,
+    // whether each local is defined yet, and where it was declared
+    pub scopes: Vec<HashMap<String, (bool, Span)>>,
     current_function: FunctionType,
     current_class: ClassType,
+    // the labels of the loops around the statement, innermost last
+    loops: Vec<Option<String>>,
 }
 
 impl<'a> Resolver<'a> {
-    pub fn new(interpreter: &'a mut Interpreter) -> Resolver {
+    pub fn new(interpreter: &'a mut Interpreter) -> Resolver<'a> {
         Resolver {
             interpreter,
             scopes: Vec::new(),
             current_function: FunctionType::None,
             current_class: ClassType::None,
+            loops: Vec::new(),
         }
     }
 
@@ -35,11 +40,28 @@ impl<'a> Resolver<'a> {
         Ok(())
     }
 
+    // Resolves every top-level statement, even after errors in earlier ones,
+    // and returns all the errors.
+    pub fn resolve_all(&mut self, statements: &[Stmt]) -> Vec<Error> {
+        let mut errors = vec![];
+        for statement in statements {
+            if let Err(error) = self.resolve_statement(statement) {
+                errors.push(error);
+                // the error may have left scopes open
+                self.scopes.clear();
+                self.current_function = FunctionType::None;
+                self.current_class = ClassType::None;
+                self.loops.clear();
+            }
+        }
+        errors
+    }
+
     fn resolve_statement(&mut self, stmt: &Stmt) -> Result<()> {
         stmt.accept(self)
     }
 
-    fn resolve_expr(&mut self, expr: &Expr) -> Result<()> {
+    pub fn resolve_expr(&mut self, expr: &Expr) -> Result<()> {
         expr.accept(self)
     }
 
@@ -52,6 +74,8 @@ impl<'a> Resolver<'a> {
     ) -> Result<()> {
         let enclosing_function = self.current_function;
         self.current_function = func_type;
+        // `break` can't leave a function
+        let enclosing_loops = std::mem::take(&mut self.loops);
 
         self.begin_scope();
         for param in params {
@@ -61,10 +85,29 @@ impl<'a> Resolver<'a> {
         self.resolve_statements(body)?;
         self.end_scope();
         self.current_function = enclosing_function;
+        self.loops = enclosing_loops;
         Ok(())
     }
 
-    fn resolve_local(&mut self, expr: Expr, name: &Token) -> Result<()> {
+    fn resolve_jump(&self, keyword: &Token, label: &Option<Token>) -> Result<()> {
+        if self.loops.is_empty() {
+            return Err(Error::ResolveError(
+                Box::new(keyword.clone()),
+                format!("Cannot use '{}' outside of a loop.", keyword.lexeme),
+            ));
+        }
+        match label {
+            Some(label) if !self.loops.contains(&Some(label.lexeme.clone())) => {
+                Err(Error::ResolveError(
+                    Box::new(label.clone()),
+                    format!("No enclosing loop is labeled '{}'.", label.lexeme),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn resolve_local(&mut self, name: &Token) -> Result<()> {
         let scopes_count = self.scopes.len();
         let mut i = scopes_count;
         loop {
@@ -73,7 +116,7 @@ impl<'a> Resolver<'a> {
             }
             if let Some(scope) = self.scopes.get(i - 1) {
                 if let Some(_r) = scope.get(&name.lexeme) {
-                    self.interpreter.resolve(expr, scopes_count - 1 - (i - 1))?;
+                    self.interpreter.resolve(name, scopes_count - 1 - (i - 1))?;
                     return Ok(());
                 }
             }
@@ -88,13 +131,14 @@ impl<'a> Resolver<'a> {
             return Ok(());
         }
         let mut scope = self.scopes.pop().unwrap();
-        if scope.contains_key(&name.lexeme) {
+        if let Some((_, declared)) = scope.get(&name.lexeme) {
             return Err(Error::ResolveError(
-                name.clone(),
+                Box::new(name.clone()),
                 String::from("Variable with this name already declared in this scope."),
-            ));
+            )
+            .with_note(Note::new("previously declared here", Some(*declared))));
         }
-        scope.insert(name.lexeme.clone(), false);
+        scope.insert(name.lexeme.clone(), (false, name.span));
         self.scopes.push(scope);
         Ok(())
     }
@@ -104,7 +148,7 @@ impl<'a> Resolver<'a> {
             return;
         }
         let mut scope = self.scopes.pop().unwrap();
-        scope.insert(name.lexeme.clone(), true);
+        scope.insert(name.lexeme.clone(), (true, name.span));
         self.scopes.push(scope);
     }
 
@@ -145,32 +189,24 @@ impl<'a> ExprVisitor<Result<()>> for Resolver<'a> {
     }
 
     fn visit_variable(&mut self, name: &Token) -> Result<()> {
-        if let Some(scope) = self.scopes.iter().peekable().peek() {
-            if let Some(var) = scope.get(&name.lexeme) {
-                if var == &false {
-                    resolve_error(
-                        name.clone(),
-                        "Cannot read local variable in its own initializer.",
-                    );
+        if let Some(scope) = self.scopes.last() {
+            if let Some((defined, declared)) = scope.get(&name.lexeme) {
+                if !defined {
                     return Err(Error::ResolveError(
-                        name.clone(),
+                        Box::new(name.clone()),
                         String::from("Cannot read local variable in its own initializer."),
-                    ));
+                    )
+                    .with_note(Note::new("variable declared here", Some(*declared))));
                 }
             }
         }
-        let expr = Expr::Variable { name: name.clone() };
-        self.resolve_local(expr, name)?;
+        self.resolve_local(name)?;
         Ok(())
     }
 
     fn visit_assign(&mut self, name: &Token, value: &Expr) -> Result<()> {
         self.resolve_expr(value)?;
-        let expr = Expr::Assign {
-            name: name.clone(),
-            value: Box::new(value.clone()),
-        };
-        self.resolve_local(expr, name)?;
+        self.resolve_local(name)?;
         Ok(())
     }
 
@@ -191,38 +227,123 @@ impl<'a> ExprVisitor<Result<()>> for Resolver<'a> {
         self.resolve_expr(object)
     }
 
-    fn visit_super(&mut self, keyword: &Token, method: &Token) -> Result<()> {
+    fn visit_super(&mut self, keyword: &Token, _method: &Token) -> Result<()> {
         match self.current_class {
             ClassType::None => Err(Error::ResolveError(
-                keyword.clone(),
+                Box::new(keyword.clone()),
                 "Cannot use 'super' outside of a class.".to_string(),
             )),
             ClassType::Class => Err(Error::ResolveError(
-                keyword.clone(),
+                Box::new(keyword.clone()),
                 "Cannot use 'super' in a class with no superclass.".to_string(),
             )),
-            ClassType::SubClass => {
-                let expr = Expr::Super {
-                    keyword: keyword.clone(),
-                    method: method.clone(),
-                };
-                self.resolve_local(expr, keyword)
-            }
+            ClassType::SubClass => self.resolve_local(keyword),
         }
     }
 
     fn visit_this(&mut self, keyword: &Token) -> Result<()> {
         if self.current_class == ClassType::None {
             return Err(Error::ResolveError(
-                keyword.clone(),
+                Box::new(keyword.clone()),
                 "Cannot use 'this' outside of a class.".to_string(),
             ));
         }
 
-        let expr = Expr::This {
-            keyword: keyword.clone(),
-        };
-        self.resolve_local(expr, keyword)
+        self.resolve_local(keyword)
+    }
+
+    fn visit_compound_assign(
+        &mut self,
+        name: &Token,
+        _operator: &Token,
+        value: &Expr,
+        _postfix: bool,
+    ) -> Result<()> {
+        self.resolve_expr(value)?;
+        self.resolve_local(name)
+    }
+
+    fn visit_compound_set(
+        &mut self,
+        object: &Expr,
+        _name: &Token,
+        _operator: &Token,
+        value: &Expr,
+        _postfix: bool,
+    ) -> Result<()> {
+        self.resolve_expr(value)?;
+        self.resolve_expr(object)
+    }
+
+    fn visit_lambda(&mut self, keyword: &Token, params: &[Token], body: &[Stmt]) -> Result<()> {
+        self.resolve_function(keyword, params, body, FunctionType::Function)
+    }
+
+    fn visit_list(&mut self, elements: &[Expr]) -> Result<()> {
+        for element in elements {
+            self.resolve_expr(element)?;
+        }
+        Ok(())
+    }
+
+    fn visit_map(&mut self, entries: &[(Expr, Expr)], _brace: &Token) -> Result<()> {
+        for (key, value) in entries {
+            self.resolve_expr(key)?;
+            self.resolve_expr(value)?;
+        }
+        Ok(())
+    }
+
+    fn visit_index(&mut self, object: &Expr, _bracket: &Token, index: &Expr) -> Result<()> {
+        self.resolve_expr(object)?;
+        self.resolve_expr(index)
+    }
+
+    fn visit_set_index(
+        &mut self,
+        object: &Expr,
+        _bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> Result<()> {
+        self.resolve_expr(object)?;
+        self.resolve_expr(index)?;
+        self.resolve_expr(value)
+    }
+
+    fn visit_slice(
+        &mut self,
+        object: &Expr,
+        _bracket: &Token,
+        start: &Option<Box<Expr>>,
+        end: &Option<Box<Expr>>,
+    ) -> Result<()> {
+        self.resolve_expr(object)?;
+        for bound in [start, end].into_iter().flatten() {
+            self.resolve_expr(bound)?;
+        }
+        Ok(())
+    }
+
+    fn visit_compound_set_index(
+        &mut self,
+        object: &Expr,
+        _bracket: &Token,
+        index: &Expr,
+        _operator: &Token,
+        value: &Expr,
+        _postfix: bool,
+    ) -> Result<()> {
+        self.resolve_expr(object)?;
+        self.resolve_expr(index)?;
+        self.resolve_expr(value)
+    }
+
+    fn visit_interpolation(&mut self, parts: &[Expr]) -> Result<()> {
+        for part in parts {
+            self.resolve_expr(part)?;
+        }
+        Ok(())
     }
 }
 
@@ -247,10 +368,10 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
     fn visit_var_stmt(&mut self, name: &Token, initializer: &Expr) -> Result<()> {
         self.declare(name)?;
         match initializer {
-            Expr::Literal { value } => match value {
-                Literal::None => {}
-                _ => self.resolve_expr(initializer)?,
-            },
+            Expr::Literal {
+                value: Literal::None,
+                ..
+            } => {}
             _ => self.resolve_expr(initializer)?,
         }
         self.define(name);
@@ -271,12 +392,54 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
         }
     }
 
-    fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> Result<()> {
+    fn visit_while_stmt(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: &Option<Box<Expr>>,
+        label: &Option<Token>,
+    ) -> Result<()> {
         self.resolve_expr(condition)?;
-        self.resolve_statement(body)?;
+        self.loops
+            .push(label.as_ref().map(|label| label.lexeme.clone()));
+        let result = self.resolve_statement(body);
+        self.loops.pop();
+        result?;
+        if let Some(increment) = increment {
+            self.resolve_expr(increment)?;
+        }
         Ok(())
     }
 
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &Token,
+        _keyword: &Token,
+        iterable: &Expr,
+        body: &Stmt,
+        label: &Option<Token>,
+    ) -> Result<()> {
+        self.resolve_expr(iterable)?;
+        // every iteration gets a fresh variable, in a scope around the body
+        self.begin_scope();
+        self.declare(name)?;
+        self.define(name);
+        self.loops
+            .push(label.as_ref().map(|label| label.lexeme.clone()));
+        let result = self.resolve_statement(body);
+        self.loops.pop();
+        self.end_scope();
+        result
+    }
+
+    fn visit_break_stmt(&mut self, keyword: &Token, label: &Option<Token>) -> Result<()> {
+        self.resolve_jump(keyword, label)
+    }
+
+    fn visit_continue_stmt(&mut self, keyword: &Token, label: &Option<Token>) -> Result<()> {
+        self.resolve_jump(keyword, label)
+    }
+
     fn visit_function_stmt(&mut self, name: &Token, params: &[Token], body: &[Stmt]) -> Result<()> {
         self.declare(name)?;
         self.define(name);
@@ -288,20 +451,20 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
     fn visit_return_stmt(&mut self, keyword: &Token, v: &Expr) -> Result<()> {
         if self.current_function == FunctionType::None {
             return Err(Error::ResolveError(
-                keyword.clone(),
+                Box::new(keyword.clone()),
                 String::from("Cannot return from top-level code."),
             ));
         }
         // If you check current_function before, you should implement the last check for
         // is_initializer inside LoxFuncti at 12.6.2 "returnint from init()" section
-        if let Expr::Literal { value } = v {
+        if let Expr::Literal { value, .. } = v {
             if value == &Literal::None {
                 return Ok(());
             }
         }
         if self.current_function == FunctionType::Initializer {
             return Err(Error::ResolveError(
-                keyword.clone(),
+                Box::new(keyword.clone()),
                 "Cannot return a value from an initializer.".to_string(),
             ));
         }
@@ -327,7 +490,7 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
             {
                 if name.lexeme == var_super_class.lexeme {
                     return Err(Error::ResolveError(
-                        var_super_class.clone(),
+                        Box::new(var_super_class.clone()),
                         String::from("A class cannot inherit from itself."),
                     ));
                 }
@@ -339,13 +502,13 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
         if super_class.is_some() {
             self.begin_scope();
             let mut scope = self.scopes.pop().unwrap();
-            scope.insert("super".to_string(), true);
+            scope.insert("super".to_string(), (true, name.span));
             self.scopes.push(scope);
         }
 
         self.begin_scope();
         let mut scope = self.scopes.pop().unwrap();
-        scope.insert("this".to_string(), true);
+        scope.insert("this".to_string(), (true, name.span));
         self.scopes.push(scope);
 
         for method in methods {
@@ -354,6 +517,7 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
                     name: func_name,
                     params,
                     body,
+                    ..
                 } => {
                     let declaration = if func_name.lexeme == "init" {
                         FunctionType::Initializer
@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 
+use unicode_xid::UnicodeXID;
+
 use crate::{
-    error,
-    token::{Literal, Token},
+    bigint::BigInt,
+    error::Error,
+    token::{FileId, Literal, Span, Token},
     token_type::TokenType,
 };
 
@@ -10,22 +13,36 @@ use crate::{
 pub struct Scanner {
     source: String,
     tokens: Vec<Token>,
+    // byte offsets of the current token and of the next character
     start: usize,
     current: usize,
     line: usize,
+    // column of the next character, counted in chars
+    column: usize,
+    start_line: usize,
+    start_column: usize,
+    file: Option<FileId>,
     keywords: HashMap<String, TokenType>,
+    errors: Vec<Error>,
+    // the `${` of every interpolated string we're inside of, innermost last
+    interpolations: Vec<Interpolation>,
+    // `///` lines waiting for the next token
+    doc: Option<String>,
 }
 
 impl Scanner {
     pub fn new(source: String) -> Scanner {
         let mut keywords = HashMap::new();
         keywords.insert(String::from("and"), TokenType::AND);
+        keywords.insert(String::from("break"), TokenType::BREAK);
         keywords.insert(String::from("class"), TokenType::CLASS);
+        keywords.insert(String::from("continue"), TokenType::CONTINUE);
         keywords.insert(String::from("else"), TokenType::ELSE);
         keywords.insert(String::from("false"), TokenType::FALSE);
         keywords.insert(String::from("for"), TokenType::FOR);
         keywords.insert(String::from("fun"), TokenType::FUN);
         keywords.insert(String::from("if"), TokenType::IF);
+        keywords.insert(String::from("in"), TokenType::IN);
         keywords.insert(String::from("nil"), TokenType::NIL);
         keywords.insert(String::from("or"), TokenType::OR);
         keywords.insert(String::from("print"), TokenType::PRINT);
@@ -41,19 +58,95 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
-            keywords: keywords,
+            column: 1,
+            start_line: 1,
+            start_column: 1,
+            file: None,
+            keywords,
+            errors: Vec::new(),
+            interpolations: Vec::new(),
+            doc: None,
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Vec<Token> {
+    // Tags the spans of all tokens with `file`.
+    pub fn with_file(mut self, file: FileId) -> Scanner {
+        self.file = Some(file);
+        self
+    }
+
+    // Scans the whole source. Characters that don't form a token are reported
+    // in the errors and skipped, so the tokens are usable for tools even when
+    // the errors aren't empty.
+    pub fn scan_tokens(&mut self) -> (Vec<Token>, Vec<Error>) {
         while !self.is_at_end() {
             self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.column;
             self.scan_token();
         }
 
-        let eof_token = Token::new(TokenType::EOF, String::from(""), Literal::None, self.line);
+        while let Some(interpolation) = self.interpolations.pop() {
+            self.error_since(interpolation.mark, "Unterminated string interpolation.");
+        }
+
+        self.start = self.current;
+        self.start_line = self.line;
+        self.start_column = self.column;
+        let eof_token = Token::new(TokenType::EOF, String::from(""), Literal::None, self.span());
         self.tokens.push(eof_token);
-        self.tokens.clone()
+        (
+            std::mem::take(&mut self.tokens),
+            std::mem::take(&mut self.errors),
+        )
+    }
+
+    fn error(&mut self, message: &str) {
+        self.errors
+            .push(Error::ScanError(self.span(), String::from(message)));
+    }
+
+    // Reports an error for the text scanned since `mark`, e.g. a bad escape
+    // sequence inside a string.
+    fn error_since(&mut self, mark: Mark, message: &str) {
+        self.errors.push(Error::ScanError(
+            self.span_since(mark),
+            String::from(message),
+        ));
+    }
+
+    // The span of the token scanned so far.
+    fn span(&self) -> Span {
+        self.span_since(Mark {
+            offset: self.start,
+            line: self.start_line,
+            column: self.start_column,
+        })
+    }
+
+    fn span_since(&self, mark: Mark) -> Span {
+        Span {
+            file: self.file,
+            start: mark.offset,
+            end: self.current,
+            line: mark.line,
+            column: mark.column,
+            end_line: self.line,
+            end_column: self.column,
+        }
+    }
+
+    fn mark(&self) -> Mark {
+        Mark {
+            offset: self.current,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn newline(&mut self) {
+        self.line += 1;
+        self.column = 1;
     }
 
     fn scan_token(&mut self) {
@@ -66,10 +159,29 @@ impl Scanner {
                 self.add_token_without_literal(TokenType::RIGHTPAREN);
             }
             '{' => {
+                if let Some(interpolation) = self.interpolations.last_mut() {
+                    interpolation.braces += 1;
+                }
                 self.add_token_without_literal(TokenType::LEFTBRACE);
             }
-            '}' => {
-                self.add_token_without_literal(TokenType::RIGHTBRACE);
+            '}' => match self.interpolations.last_mut() {
+                // the end of an interpolated expression, the string goes on
+                Some(interpolation) if interpolation.braces == 0 => {
+                    let triple = interpolation.triple;
+                    self.interpolations.pop();
+                    self.string_contents(triple, true);
+                }
+                Some(interpolation) => {
+                    interpolation.braces -= 1;
+                    self.add_token_without_literal(TokenType::RIGHTBRACE);
+                }
+                None => self.add_token_without_literal(TokenType::RIGHTBRACE),
+            },
+            '[' => {
+                self.add_token_without_literal(TokenType::LEFTBRACKET);
+            }
+            ']' => {
+                self.add_token_without_literal(TokenType::RIGHTBRACKET);
             }
             ',' => {
                 self.add_token_without_literal(TokenType::COMMA);
@@ -78,16 +190,56 @@ impl Scanner {
                 self.add_token_without_literal(TokenType::DOT);
             }
             '-' => {
-                self.add_token_without_literal(TokenType::MINUS);
+                if self.match_to_expected('-') {
+                    self.add_token_without_literal(TokenType::MINUSMINUS);
+                } else if self.match_to_expected('=') {
+                    self.add_token_without_literal(TokenType::MINUSEQUAL);
+                } else {
+                    self.add_token_without_literal(TokenType::MINUS);
+                }
             }
             '+' => {
-                self.add_token_without_literal(TokenType::PLUS);
+                if self.match_to_expected('+') {
+                    self.add_token_without_literal(TokenType::PLUSPLUS);
+                } else if self.match_to_expected('=') {
+                    self.add_token_without_literal(TokenType::PLUSEQUAL);
+                } else {
+                    self.add_token_without_literal(TokenType::PLUS);
+                }
             }
             ';' => {
                 self.add_token_without_literal(TokenType::SEMICOLON);
             }
+            ':' => {
+                self.add_token_without_literal(TokenType::COLON);
+            }
             '*' => {
-                self.add_token_without_literal(TokenType::STAR);
+                if self.match_to_expected('*') {
+                    self.add_token_without_literal(TokenType::STARSTAR);
+                } else if self.match_to_expected('=') {
+                    self.add_token_without_literal(TokenType::STAREQUAL);
+                } else {
+                    self.add_token_without_literal(TokenType::STAR);
+                }
+            }
+            '%' => {
+                self.add_token_without_literal(TokenType::PERCENT);
+            }
+            '~' => {
+                if self.match_to_expected('/') {
+                    self.add_token_without_literal(TokenType::TILDESLASH);
+                } else {
+                    self.add_token_without_literal(TokenType::TILDE);
+                }
+            }
+            '&' => {
+                self.add_token_without_literal(TokenType::AMPERSAND);
+            }
+            '|' => {
+                self.add_token_without_literal(TokenType::PIPE);
+            }
+            '^' => {
+                self.add_token_without_literal(TokenType::CARET);
             }
             '!' => {
                 if self.match_to_expected('=') {
@@ -99,6 +251,8 @@ impl Scanner {
             '=' => {
                 if self.match_to_expected('=') {
                     self.add_token_without_literal(TokenType::EQUALEQUAL);
+                } else if self.match_to_expected('>') {
+                    self.add_token_without_literal(TokenType::ARROW);
                 } else {
                     self.add_token_without_literal(TokenType::EQUAL);
                 }
@@ -106,6 +260,8 @@ impl Scanner {
             '<' => {
                 if self.match_to_expected('=') {
                     self.add_token_without_literal(TokenType::LESSEQUAL);
+                } else if self.match_to_expected('<') {
+                    self.add_token_without_literal(TokenType::LESSLESS);
                 } else {
                     self.add_token_without_literal(TokenType::LESS);
                 }
@@ -113,15 +269,27 @@ impl Scanner {
             '>' => {
                 if self.match_to_expected('=') {
                     self.add_token_without_literal(TokenType::GREATEREQUAL);
+                } else if self.match_to_expected('>') {
+                    self.add_token_without_literal(TokenType::GREATERGREATER);
                 } else {
                     self.add_token_without_literal(TokenType::GREATER);
                 }
             }
             '/' => {
                 if self.match_to_expected('/') {
+                    // `///` but not `////`, the same as in Rust
+                    let doc = self.peek() == '/' && self.peek_next() != '/';
+                    let text_start = self.current + 1;
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                    if doc {
+                        self.doc_comment(text_start);
+                    }
+                } else if self.match_to_expected('*') {
+                    self.block_comment();
+                } else if self.match_to_expected('=') {
+                    self.add_token_without_literal(TokenType::SLASHEQUAL);
                 } else {
                     self.add_token_without_literal(TokenType::SLASH);
                 }
@@ -131,33 +299,31 @@ impl Scanner {
             '\r' => {}
             '\t' => {}
             '\n' => {
-                self.line += 1;
+                self.newline();
             }
 
             '"' => {
                 self.string();
             }
+            'r' if matches!(self.peek(), '"' | '#') => self.raw_string(),
 
             '0'..='9' => self.number(),
 
-            'a'..='z' | 'A'..='Z' | '_' => self.identifier(),
+            c if self.is_alpha(c) => self.identifier(),
 
             _ => {
-                error::scanner_error(self.line, "Unexpected character.");
+                self.error("Unexpected character.");
             }
         }
     }
 
     //文字列の現在の文字が期待された文字と一致するかどうかをチェックし、一致する場合にのみカーソルを進めるために使用されます
     fn match_to_expected(&mut self, expected: char) -> bool {
-        if self.is_at_end() {
+        if self.peek() != expected {
             return false;
         }
-        if self.source.chars().nth(self.current).unwrap() != expected {
-            return false;
-        }
-        self.current += 1;
-        return true;
+        self.advance();
+        true
     }
 
     fn add_token_without_literal(&mut self, token_type: TokenType) {
@@ -166,93 +332,319 @@ impl Scanner {
 
     fn add_token(&mut self, token_type: TokenType, literal: Literal) {
         let text = &self.source[self.start..self.current];
-        self.tokens.push(Token::new(
-            token_type,
-            String::from(text),
-            literal,
-            self.line,
-        ))
+        let mut token = Token::new(token_type, String::from(text), literal, self.span());
+        token.doc = self.doc.take();
+        self.tokens.push(token)
+    }
+
+    // Adds the text of a `///` line to the doc of the next token.
+    fn doc_comment(&mut self, text_start: usize) {
+        let line = self.source[text_start..self.current].trim_end_matches('\r');
+        let line = line.strip_prefix(' ').unwrap_or(line);
+        match &mut self.doc {
+            Some(doc) => {
+                doc.push('\n');
+                doc.push_str(line);
+            }
+            None => self.doc = Some(line.to_string()),
+        }
+    }
+
+    // `/* ... */`, which may contain other block comments.
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                self.error("Unterminated block comment.");
+                return;
+            }
+            let rest = &self.source[self.current..];
+            if rest.starts_with("/*") {
+                depth += 1;
+                self.advance();
+                self.advance();
+            } else if rest.starts_with("*/") {
+                depth -= 1;
+                self.advance();
+                self.advance();
+            } else if self.advance() == '\n' {
+                self.newline();
+            }
+        }
     }
 
     fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
 
+    // Returns '\0' without moving once the end is reached.
     fn advance(&mut self) -> char {
-        self.current += 1;
-        self.source
-            .chars()
-            .nth((self.current - 1).try_into().unwrap())
-            .unwrap()
+        let c = self.peek();
+        if self.is_at_end() {
+            return c;
+        }
+        self.current += c.len_utf8();
+        self.column += 1;
+        c
     }
 
+    // `"..."`, or `"""..."""` which may contain unescaped quotes. A line break
+    // right after the opening quotes of a triple-quoted string is dropped.
     fn string(&mut self) {
-        while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+        let triple = self.peek() == '"' && self.peek_next() == '"';
+        if triple {
+            self.advance();
+            self.advance();
+            self.match_to_expected('\r');
+            if self.match_to_expected('\n') {
+                self.newline();
+            }
+        }
+        self.string_contents(triple, false);
+    }
+
+    // Scans up to the closing quotes, or up to a `${` which makes this part
+    // an INTERPOLATION token. The string resumes at the matching `}`, and its
+    // last part is an INTERPOLATIONEND token.
+    fn string_contents(&mut self, triple: bool, resumed: bool) {
+        let terminator = if triple { "\"\"\"" } else { "\"" };
+
+        let mut value = String::new();
+        while !self.source[self.current..].starts_with(terminator) {
+            if self.is_at_end() {
+                self.error("Unterminated string.");
+                return;
+            }
+            let mark = self.mark();
+            if self.source[self.current..].starts_with("${") {
+                self.advance();
+                self.advance();
+                self.interpolations.push(Interpolation {
+                    mark,
+                    triple,
+                    braces: 0,
+                });
+                self.add_token(TokenType::INTERPOLATION, Literal::String(value));
+                return;
+            }
+            match self.advance() {
+                '\\' => self.escape(mark, &mut value),
+                '\n' => {
+                    self.newline();
+                    value.push('\n');
+                }
+                c => value.push(c),
             }
+        }
+        for _ in 0..terminator.len() {
             self.advance();
         }
+        let token_type = if resumed {
+            TokenType::INTERPOLATIONEND
+        } else {
+            TokenType::STRING
+        };
+        self.add_token(token_type, Literal::String(value));
+    }
 
-        if self.is_at_end() {
-            error::scanner_error(self.line, "Unterminated string.");
+    // Decodes the escape sequence after a backslash at `mark`. Bad escapes are
+    // reported and left out of the string.
+    fn escape(&mut self, mark: Mark, value: &mut String) {
+        let c = match self.advance() {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '"' => '"',
+            '\'' => '\'',
+            '$' => '$',
+            '\\' => '\\',
+            'u' => match self.unicode_escape(mark) {
+                Some(c) => c,
+                None => return,
+            },
+            // reported as an unterminated string instead
+            '\0' if self.is_at_end() => return,
+            c => {
+                if c == '\n' {
+                    self.newline();
+                }
+                self.error_since(
+                    mark,
+                    &format!("Unknown escape sequence '\\{}'.", c.escape_default()),
+                );
+                return;
+            }
+        };
+        value.push(c);
+    }
+
+    // `\u{1F600}`: one to six hex digits naming a Unicode scalar value.
+    fn unicode_escape(&mut self, mark: Mark) -> Option<char> {
+        if !self.match_to_expected('{') {
+            self.error_since(mark, "Expect '{' after '\\u'.");
+            return None;
+        }
+        let digits_start = self.current;
+        while self.peek().is_ascii_hexdigit() {
+            self.advance();
+        }
+        let digits = self.source[digits_start..self.current].to_string();
+        if digits.is_empty() || digits.len() > 6 || !self.match_to_expected('}') {
+            self.error_since(
+                mark,
+                "Invalid unicode escape, expect 1 to 6 hex digits in braces.",
+            );
+            return None;
+        }
+        let c = u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32);
+        if c.is_none() {
+            self.error_since(mark, "Invalid unicode escape, not a Unicode scalar value.");
         }
+        c
+    }
 
-        self.advance();
+    // `r"..."` keeps backslashes as they are. To include a quote, put the
+    // same number of '#' around the quotes: `r#"say "hi""#`.
+    fn raw_string(&mut self) {
+        let mut hashes = 0;
+        while self.match_to_expected('#') {
+            hashes += 1;
+        }
+        if !self.match_to_expected('"') {
+            self.error("Expect '\"' to start raw string.");
+            return;
+        }
+        let terminator = format!("\"{}", "#".repeat(hashes));
 
-        let value = String::from(self.source.get(self.start + 1..self.current - 1).unwrap());
+        let content_start = self.current;
+        while !self.source[self.current..].starts_with(&terminator) {
+            if self.is_at_end() {
+                self.error("Unterminated string.");
+                return;
+            }
+            if self.advance() == '\n' {
+                self.newline();
+            }
+        }
+        let value = self.source[content_start..self.current].to_string();
+        for _ in 0..terminator.len() {
+            self.advance();
+        }
         self.add_token(TokenType::STRING, Literal::String(value));
     }
 
+    // Decimal numbers with an optional fraction and exponent (`2.5E3`,
+    // `1e-9`), or integers in hex, binary or octal (`0xFF`, `0b1010`, `0o17`).
+    // Underscores may separate digits: `1_000_000`.
     fn number(&mut self) {
-        while self.is_digit(self.peek()) {
+        let radix = match (&self.source[self.start..self.current], self.peek()) {
+            ("0", 'x' | 'X') => Some((16, "hexadecimal")),
+            ("0", 'b' | 'B') => Some((2, "binary")),
+            ("0", 'o' | 'O') => Some((8, "octal")),
+            _ => None,
+        };
+        if let Some((radix, name)) = radix {
             self.advance();
+            return self.radix_number(radix, name);
         }
 
+        self.digits();
+        let mut float = false;
         if self.peek() == '.' && self.is_digit(self.peek_next()) {
+            float = true;
             self.advance();
-
-            while self.is_digit(self.peek()) {
+            self.digits();
+        }
+        let exponent_digit = match self.peek_next() {
+            '+' | '-' => self.source[self.current..]
+                .chars()
+                .nth(2)
+                .is_some_and(|c| c.is_ascii_digit()),
+            c => c.is_ascii_digit(),
+        };
+        if matches!(self.peek(), 'e' | 'E') && exponent_digit {
+            float = true;
+            self.advance();
+            if matches!(self.peek(), '+' | '-') {
                 self.advance();
             }
+            self.digits();
+        }
+        if !self.number_suffix_is_empty() {
+            return;
         }
 
-        let target_literal = self.source.get(self.start..self.current).unwrap();
-        //println!("target_literal: {}", target_literal);
-
-        let parsed_usize = target_literal.parse::<isize>();
-        let parsed_float = target_literal.parse::<f64>();
-
-        let literal = if parsed_usize.is_ok() {
-            Literal::Isize(parsed_usize.ok().unwrap())
-        } else if parsed_float.is_ok() {
-            Literal::Float(parsed_float.ok().unwrap())
+        let text = self.source[self.start..self.current].replace('_', "");
+        let literal = if float {
+            match text.parse::<f64>() {
+                Ok(f) if f.is_finite() => Literal::Float(f),
+                _ => {
+                    self.error("Number literal is too large to be represented.");
+                    return;
+                }
+            }
         } else {
-            error::scanner_error(self.line, "Unexpected character.");
-            panic!("")
+            integer_literal(&text, 10)
         };
         self.add_token(TokenType::NUMBER, literal)
     }
 
+    fn radix_number(&mut self, radix: u32, name: &str) {
+        let digits_start = self.current;
+        while self.is_alpha_numeric(self.peek()) {
+            self.advance();
+        }
+        let digits = self.source[digits_start..self.current].replace('_', "");
+
+        if digits.is_empty() {
+            self.error(&format!("Expect digits in {} literal.", name));
+            return;
+        }
+        if let Some(c) = digits.chars().find(|c| !c.is_digit(radix)) {
+            self.error(&format!("Invalid digit '{}' in {} literal.", c, name));
+            return;
+        }
+        let literal = integer_literal(&digits, radix);
+        self.add_token(TokenType::NUMBER, literal);
+    }
+
+    fn digits(&mut self) {
+        while self.is_digit(self.peek()) || self.peek() == '_' {
+            self.advance();
+        }
+    }
+
+    // Reports letters right after a number, e.g. `12abc` or `1e`.
+    fn number_suffix_is_empty(&mut self) -> bool {
+        let suffix_start = self.current;
+        while self.is_alpha_numeric(self.peek()) {
+            self.advance();
+        }
+        if suffix_start == self.current {
+            return true;
+        }
+        let suffix = self.source[suffix_start..self.current].to_string();
+        self.error(&format!("Invalid suffix '{}' for number literal.", suffix));
+        false
+    }
+
     fn is_digit(&mut self, c: char) -> bool {
-        c >= '0' && c <= '9'
+        c.is_ascii_digit()
     }
 
     //文字を消費しない先読み
     //現在の文字を返しますが、ファイルの終わりに達している場合はnull文字を返します
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
-        }
-        self.source.chars().nth(self.current).unwrap()
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        }
-
-        return self.source.chars().nth(self.current + 1).unwrap();
+        let mut chars = self.source[self.current..].chars();
+        chars.next();
+        chars.next().unwrap_or('\0')
     }
 
     fn identifier(&mut self) {
@@ -260,17 +652,306 @@ impl Scanner {
             self.advance();
         }
 
-        let text = self.source.get(self.start..self.current).unwrap();
+        let text = &self.source[self.start..self.current];
+        let token = *self.keywords.get(text).unwrap_or(&TokenType::IDENTIFIER);
+        self.add_token_without_literal(token);
+    }
 
-        let keywords = self.keywords.clone();
-        let token = keywords.get(text).unwrap_or(&TokenType::IDENTIFIER);
-        self.add_token_without_literal(token.clone());
+    // Identifiers follow Unicode's XID rules, like Rust's, so `名前` works too.
+    fn is_alpha(&self, c: char) -> bool {
+        c == '_' || c.is_xid_start()
     }
 
     fn is_alpha_numeric(&self, c: char) -> bool {
-        match c {
-            'a'..='z' | 'A'..='Z' | '_' | '0'..='9' => true,
-            _ => false,
+        c.is_xid_continue()
+    }
+}
+
+// Integers too large for an `isize` become big integers. `digits` must be
+// valid in `radix`.
+fn integer_literal(digits: &str, radix: u32) -> Literal {
+    match isize::from_str_radix(digits, radix) {
+        Ok(i) => Literal::Isize(i),
+        Err(_) => Literal::BigInt(BigInt::from_str_radix(digits, radix).unwrap_or_default()),
+    }
+}
+
+// A position in the source, for errors that start inside a token.
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Interpolation {
+    mark: Mark,
+    triple: bool,
+    // unmatched '{' inside the interpolated expression
+    braces: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> (Vec<Token>, Vec<Error>) {
+        Scanner::new(String::from(source)).scan_tokens()
+    }
+
+    fn errors(source: &str) -> Vec<String> {
+        scan(source)
+            .1
+            .iter()
+            .map(|error| error.to_string())
+            .collect()
+    }
+
+    fn types(source: &str) -> Vec<TokenType> {
+        let (tokens, errors) = scan(source);
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+        tokens.iter().map(|token| token.token_type).collect()
+    }
+
+    // The value of the string literal that `source` starts with.
+    fn string(source: &str) -> String {
+        let (tokens, errors) = scan(source);
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+        match &tokens[0].literal {
+            Literal::String(value) => value.clone(),
+            literal => panic!("expected a string, got {:?}", literal),
         }
     }
+
+    fn number(source: &str) -> Literal {
+        let (tokens, errors) = scan(source);
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+        assert_eq!(tokens[0].token_type, TokenType::NUMBER);
+        tokens[0].literal.clone()
+    }
+
+    #[test]
+    fn spans_have_offsets_lines_and_columns() {
+        let tokens = Scanner::new(String::from("var x;\n  x = \"a\nb\";"))
+            .with_file(7)
+            .scan_tokens()
+            .0;
+        let x = &tokens[3].span;
+        assert_eq!((x.file, x.start, x.end), (Some(7), 9, 10));
+        assert_eq!((x.line, x.column, x.end_line, x.end_column), (2, 3, 2, 4));
+        let string = &tokens[5].span;
+        assert_eq!((string.line, string.column), (2, 7));
+        assert_eq!((string.end_line, string.end_column), (3, 3));
+        let eof = &tokens.last().unwrap().span;
+        assert_eq!((eof.line, eof.column, eof.start, eof.end), (3, 4, 19, 19));
+    }
+
+    #[test]
+    fn errors_are_collected_and_scanning_goes_on() {
+        let (tokens, errors) = scan("var a = @;\nvar b = #;");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].to_string(),
+            "[line 1:9] Error: Unexpected character."
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "[line 2:9] Error: Unexpected character."
+        );
+        assert_eq!(
+            tokens
+                .iter()
+                .filter(|t| t.token_type == TokenType::VAR)
+                .count(),
+            2
+        );
+        assert_eq!(tokens.last().unwrap().token_type, TokenType::EOF);
+    }
+
+    #[test]
+    fn unterminated_strings_and_comments_are_errors() {
+        assert_eq!(
+            errors("print \"abc"),
+            vec!["[line 1:7] Error: Unterminated string."]
+        );
+        assert_eq!(
+            errors("/* never closed"),
+            vec!["[line 1:1] Error: Unterminated block comment."]
+        );
+    }
+
+    #[test]
+    fn malformed_input_does_not_panic() {
+        for source in [
+            "\"",
+            "0x",
+            "1e",
+            "1.",
+            "r#",
+            "\"\\u{",
+            "/*/",
+            "\"${",
+            "\u{1F600}",
+        ] {
+            let (tokens, _) = scan(source);
+            assert_eq!(tokens.last().unwrap().token_type, TokenType::EOF);
+        }
+    }
+
+    #[test]
+    fn identifiers_may_use_unicode_letters() {
+        let (tokens, _) = scan("café 变量 _x1 ǅungla");
+        let names: Vec<&str> = tokens.iter().map(|t| t.lexeme.as_str()).collect();
+        assert_eq!(names, ["café", "变量", "_x1", "ǅungla", ""]);
+        assert_eq!(types("naïve"), vec![TokenType::IDENTIFIER, TokenType::EOF]);
+    }
+
+    #[test]
+    fn symbols_are_not_identifiers() {
+        assert_eq!(
+            errors("😀"),
+            vec!["[line 1:1] Error: Unexpected character."]
+        );
+        assert_eq!(
+            errors("x😀"),
+            vec!["[line 1:2] Error: Unexpected character."]
+        );
+    }
+
+    #[test]
+    fn columns_count_characters_not_bytes() {
+        let (tokens, _) = scan("变量 = ü;");
+        let semicolon = &tokens[3].span;
+        assert_eq!((semicolon.column, semicolon.start), (7, 11));
+    }
+
+    #[test]
+    fn escape_sequences_are_decoded() {
+        assert_eq!(string(r#""a\n\t\r\0\"\'\$\\b""#), "a\n\t\r\0\"'$\\b");
+        assert_eq!(string(r#""\u{48}\u{e9}\u{1F600}""#), "Hé😀");
+    }
+
+    #[test]
+    fn bad_escapes_are_reported_where_they_start() {
+        assert_eq!(
+            errors(r#""ab\q""#),
+            vec![r"[line 1:4] Error: Unknown escape sequence '\q'."]
+        );
+        assert_eq!(
+            errors(r#""\u41""#),
+            vec![r"[line 1:2] Error: Expect '{' after '\u'."]
+        );
+        assert_eq!(
+            errors(r#""\u{}" "\u{1234567}""#),
+            vec![
+                "[line 1:2] Error: Invalid unicode escape, expect 1 to 6 hex digits in braces.",
+                "[line 1:9] Error: Invalid unicode escape, expect 1 to 6 hex digits in braces.",
+            ]
+        );
+        assert_eq!(
+            errors(r#""\u{D800}""#),
+            vec!["[line 1:2] Error: Invalid unicode escape, not a Unicode scalar value."]
+        );
+    }
+
+    #[test]
+    fn raw_strings_keep_backslashes() {
+        assert_eq!(string(r#"r"C:\temp\n""#), r"C:\temp\n");
+        assert_eq!(string(r###"r#"say "hi""#"###), r#"say "hi""#);
+        assert_eq!(string(r###"r##"a "# b"##"###), r##"a "# b"##);
+        assert_eq!(
+            errors(r##"r#"open"##),
+            vec!["[line 1:1] Error: Unterminated string."]
+        );
+    }
+
+    #[test]
+    fn triple_quoted_strings_span_lines_and_hold_quotes() {
+        assert_eq!(string("\"\"\"\nsay \"hi\"\nbye\"\"\""), "say \"hi\"\nbye");
+        let (tokens, _) = scan("\"\"\"a\nb\"\"\" x");
+        assert_eq!(tokens[1].span.line, 2);
+    }
+
+    #[test]
+    fn block_comments_nest() {
+        assert_eq!(
+            types("a /* one /* two */ still one */ b"),
+            vec![TokenType::IDENTIFIER, TokenType::IDENTIFIER, TokenType::EOF]
+        );
+        let (tokens, _) = scan("/* a\n/* b\n*/\n*/ x");
+        assert_eq!(tokens[0].span.line, 4);
+        assert_eq!(
+            errors("/* /* */"),
+            vec!["[line 1:1] Error: Unterminated block comment."]
+        );
+    }
+
+    #[test]
+    fn doc_comments_go_to_the_next_token() {
+        let (tokens, _) = scan("/// First.\n///Second.\r\nfun f");
+        assert_eq!(tokens[0].doc.as_deref(), Some("First.\nSecond."));
+        assert_eq!(tokens[1].doc, None);
+    }
+
+    #[test]
+    fn integers_in_other_bases() {
+        assert_eq!(number("0xFF"), Literal::Isize(255));
+        assert_eq!(number("0Xff"), Literal::Isize(255));
+        assert_eq!(number("0b1010"), Literal::Isize(10));
+        assert_eq!(number("0o17"), Literal::Isize(15));
+        assert_eq!(number("0x_dead_beef"), Literal::Isize(0xdead_beef));
+    }
+
+    #[test]
+    fn underscores_and_exponents() {
+        assert_eq!(number("1_000_000"), Literal::Isize(1_000_000));
+        assert_eq!(number("2.5E3"), Literal::Float(2500.0));
+        assert_eq!(number("1e-9"), Literal::Float(1e-9));
+        assert_eq!(number("1_0.2_5e+1"), Literal::Float(102.5));
+        assert_eq!(
+            types("1.foo"),
+            vec![
+                TokenType::NUMBER,
+                TokenType::DOT,
+                TokenType::IDENTIFIER,
+                TokenType::EOF
+            ]
+        );
+    }
+
+    #[test]
+    fn integers_too_large_for_isize_are_big() {
+        assert_eq!(
+            number("9223372036854775808"),
+            Literal::BigInt(BigInt::from_str_radix("9223372036854775808", 10).unwrap())
+        );
+        assert_eq!(
+            number("0xFFFFFFFFFFFFFFFFFF"),
+            Literal::BigInt(BigInt::from_str_radix("FFFFFFFFFFFFFFFFFF", 16).unwrap())
+        );
+    }
+
+    #[test]
+    fn malformed_numbers_are_errors() {
+        assert_eq!(
+            errors("0x"),
+            vec!["[line 1:1] Error: Expect digits in hexadecimal literal."]
+        );
+        assert_eq!(
+            errors("0b102"),
+            vec!["[line 1:1] Error: Invalid digit '2' in binary literal."]
+        );
+        assert_eq!(
+            errors("12abc 1e"),
+            vec![
+                "[line 1:1] Error: Invalid suffix 'abc' for number literal.",
+                "[line 1:7] Error: Invalid suffix 'e' for number literal.",
+            ]
+        );
+        assert_eq!(
+            errors("1e999"),
+            vec!["[line 1:1] Error: Number literal is too large to be represented."]
+        );
+    }
 }
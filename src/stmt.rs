@@ -1,4 +1,7 @@
-use crate::{expr::Expr, token::Token};
+use crate::{
+    expr::Expr,
+    token::{Span, Token},
+};
 
 pub trait Visitor<T> {
     fn visit_expression_stmt(&mut self, expression: &Expr) -> T;
@@ -11,7 +14,23 @@ pub trait Visitor<T> {
         then_branch: &Stmt,
         else_branch: &Option<Box<Stmt>>,
     ) -> T;
-    fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> T;
+    fn visit_while_stmt(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: &Option<Box<Expr>>,
+        label: &Option<Token>,
+    ) -> T;
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &Token,
+        keyword: &Token,
+        iterable: &Expr,
+        body: &Stmt,
+        label: &Option<Token>,
+    ) -> T;
+    fn visit_break_stmt(&mut self, keyword: &Token, label: &Option<Token>) -> T;
+    fn visit_continue_stmt(&mut self, keyword: &Token, label: &Option<Token>) -> T;
     fn visit_function_stmt(&mut self, name: &Token, params: &[Token], body: &[Stmt]) -> T;
     fn visit_return_stmt(&mut self, keyword: &Token, value: &Expr) -> T;
     fn visit_class_stmt(&mut self, name: &Token, super_class: &Option<Expr>, methods: &[Stmt])
@@ -31,8 +50,12 @@ pub enum Stmt {
         name: Token,
         params: Vec<Token>,
         body: Vec<Stmt>,
+        doc: Option<String>,
+        // from `fun`, or the name of a method, to `}`
+        span: Span,
     },
     Print {
+        keyword: Token,
         expression: Expr,
     },
     Return {
@@ -42,9 +65,13 @@ pub enum Stmt {
     Var {
         name: Token,
         initializer: Expr,
+        // from `var` to `;`
+        span: Span,
     },
     Block {
         statements: Vec<Stmt>,
+        // from `{` to `}`
+        span: Span,
     },
     Class {
         name: Token,
@@ -52,40 +79,245 @@ pub enum Stmt {
         super_class: Option<Expr>,
         // Note: only for Stmt::Funtion
         methods: Vec<Stmt>,
+        doc: Option<String>,
+        // from `class` to `}`
+        span: Span,
     },
     If {
+        keyword: Token,
         condition: Expr,
         then_branch: Box<Stmt>,
         else_branch: Option<Box<Stmt>>,
     },
+    // `keyword` is the `while`, or the `for` of a loop desugared to this
     While {
+        keyword: Token,
         condition: Expr,
         body: Box<Stmt>,
+        // the third clause of a `for` loop, also run on `continue`
+        increment: Option<Box<Expr>>,
+        // `outer` in `outer: while (...)`
+        label: Option<Token>,
+    },
+    // `for (var name in iterable) body`, where the keyword is the `in`
+    ForIn {
+        name: Token,
+        keyword: Token,
+        iterable: Box<Expr>,
+        body: Box<Stmt>,
+        label: Option<Token>,
+    },
+    Break {
+        keyword: Token,
+        label: Option<Token>,
+    },
+    Continue {
+        keyword: Token,
+        label: Option<Token>,
     },
 }
 
+impl Stmt {
+    // The `///` comment written before a function, method or class.
+    pub fn doc(&self) -> Option<&str> {
+        match self {
+            Stmt::Function { doc, .. } | Stmt::Class { doc, .. } => doc.as_deref(),
+            _ => None,
+        }
+    }
+
+    // The source range covered by the statement's tokens and expressions.
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Expression { expression } => expression.span(),
+            Stmt::Print {
+                keyword,
+                expression,
+            } => keyword.span.merge(expression.span()),
+            Stmt::Function { span, .. } => *span,
+            Stmt::Return { keyword, value } => keyword.span.merge(value.span()),
+            Stmt::Var { span, .. } | Stmt::Block { span, .. } | Stmt::Class { span, .. } => *span,
+            Stmt::If {
+                keyword,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let span = keyword.span.merge(then_branch.span());
+                match else_branch {
+                    Some(else_branch) => span.merge(else_branch.span()),
+                    None => span,
+                }
+            }
+            Stmt::While {
+                keyword,
+                body,
+                label,
+                ..
+            } => {
+                let span = keyword.span.merge(body.span());
+                match label {
+                    Some(label) => span.merge(label.span),
+                    None => span,
+                }
+            }
+            Stmt::ForIn {
+                name,
+                iterable,
+                body,
+                label,
+                ..
+            } => {
+                let span = name.span.merge(iterable.span()).merge(body.span());
+                match label {
+                    Some(label) => span.merge(label.span),
+                    None => span,
+                }
+            }
+            Stmt::Break { keyword, label } | Stmt::Continue { keyword, label } => match label {
+                Some(label) => keyword.span.merge(label.span),
+                None => keyword.span,
+            },
+        }
+    }
+}
+
 impl<T> Acceptor<T> for Stmt {
     fn accept(&self, visitor: &mut dyn Visitor<T>) -> T {
         match self {
-            Stmt::Print { expression } => visitor.visit_print_stmt(expression),
+            Stmt::Print { expression, .. } => visitor.visit_print_stmt(expression),
             Stmt::Expression { expression } => visitor.visit_expression_stmt(expression),
-            Stmt::Var { name, initializer } => visitor.visit_var_stmt(name, &initializer),
-            Stmt::Block { statements } => visitor.visit_block_stmt(statements),
+            Stmt::Var {
+                name, initializer, ..
+            } => visitor.visit_var_stmt(name, initializer),
+            Stmt::Block { statements, .. } => visitor.visit_block_stmt(statements),
             Stmt::If {
                 condition,
                 then_branch,
                 else_branch,
+                ..
             } => visitor.visit_if_stmt(condition, then_branch, else_branch),
-            Stmt::While { condition, body } => visitor.visit_while_stmt(condition, body),
-            Stmt::Function { name, params, body } => {
-                visitor.visit_function_stmt(name, params, body)
-            }
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                label,
+                ..
+            } => visitor.visit_while_stmt(condition, body, increment, label),
+            Stmt::ForIn {
+                name,
+                keyword,
+                iterable,
+                body,
+                label,
+            } => visitor.visit_for_in_stmt(name, keyword, iterable, body, label),
+            Stmt::Break { keyword, label } => visitor.visit_break_stmt(keyword, label),
+            Stmt::Continue { keyword, label } => visitor.visit_continue_stmt(keyword, label),
+            Stmt::Function {
+                name, params, body, ..
+            } => visitor.visit_function_stmt(name, params, body),
             Stmt::Return { keyword, value } => visitor.visit_return_stmt(keyword, value),
             Stmt::Class {
                 name,
                 super_class,
                 methods,
+                ..
             } => visitor.visit_class_stmt(name, super_class, methods),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Stmt;
+    use crate::{parser::Parser, scanner::Scanner};
+
+    fn parse(source: &str) -> Vec<Stmt> {
+        let (tokens, errors) = Scanner::new(source.to_string()).scan_tokens();
+        assert!(errors.is_empty());
+        let (statements, errors) = Parser::new(tokens).parse();
+        assert!(errors.is_empty());
+        statements
+    }
+
+    // The source text covered by the first statement of `source`.
+    fn spanned(source: &str) -> &str {
+        let span = parse(source)[0].span();
+        &source[span.start..span.end]
+    }
+
+    #[test]
+    fn spans_start_at_the_keyword() {
+        assert_eq!(spanned("print 1 + 2;"), "print 1 + 2");
+        assert_eq!(spanned("if (x) y; else z;"), "if (x) y; else z");
+        assert_eq!(spanned("while (x) y;"), "while (x) y");
+    }
+
+    #[test]
+    fn declaration_spans_cover_the_keyword_and_the_closing_token() {
+        assert_eq!(
+            spanned("fun add(a, b) { return a + b; } print 1;"),
+            "fun add(a, b) { return a + b; }"
+        );
+        assert_eq!(spanned("fun f() {}"), "fun f() {}");
+        assert_eq!(
+            spanned("class P < Q { init() { this.x = 1; } }"),
+            "class P < Q { init() { this.x = 1; } }"
+        );
+        assert_eq!(spanned("var f;"), "var f;");
+        assert_eq!(spanned("var x = 1 + 2; x;"), "var x = 1 + 2;");
+        match &parse("class P {\n  norm() {}\n}")[0] {
+            Stmt::Class { methods, .. } => {
+                let span = methods[0].span();
+                assert_eq!((span.line, span.column), (2, 3));
+                assert_eq!((span.end_line, span.end_column), (2, 12));
+            }
+            statement => panic!("expected a class, got {:?}", statement),
+        }
+    }
+
+    #[test]
+    fn block_spans_cover_the_braces() {
+        assert_eq!(spanned("  { print 1; }  "), "{ print 1; }");
+        assert_eq!(spanned("{}"), "{}");
+        let source = "\n  {\n  }";
+        let (tokens, _) = Scanner::new(source.to_string()).scan_tokens();
+        let (statements, _) = Parser::new(tokens).parse();
+        let span = statements[0].span();
+        assert_eq!((span.line, span.column), (2, 3));
+        assert_eq!((span.end_line, span.end_column), (3, 4));
+    }
+
+    #[test]
+    fn for_loops_span_from_for() {
+        assert_eq!(
+            spanned("for (var i = 0; i < 3; i = i + 1) print i;"),
+            "for (var i = 0; i < 3; i = i + 1) print i"
+        );
+        assert_eq!(spanned("outer: while (x) {}"), "outer: while (x) {}");
+    }
+
+    #[test]
+    fn doc_comments_attach_to_functions_classes_and_methods() {
+        let statements = parse(
+            "/// Adds.\n/// Twice.\nfun add(a, b) { return a + b; }\n\
+             /// A point.\nclass Point {\n  /// Its length.\n  norm() {}\n  other() {}\n}",
+        );
+        assert_eq!(statements[0].doc(), Some("Adds.\nTwice."));
+        assert_eq!(statements[1].doc(), Some("A point."));
+        match &statements[1] {
+            Stmt::Class { methods, .. } => {
+                assert_eq!(methods[0].doc(), Some("Its length."));
+                assert_eq!(methods[1].doc(), None);
+            }
+            statement => panic!("expected a class, got {:?}", statement),
+        }
+    }
+
+    #[test]
+    fn plain_comments_are_not_docs() {
+        let statements = parse("// not a doc\n//// nor this\n/* nor */ fun f() {}");
+        assert_eq!(statements[0].doc(), None);
+        assert_eq!(parse("/// only for f\nvar x;\nfun f() {}")[1].doc(), None);
+    }
+}
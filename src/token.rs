@@ -1,10 +1,12 @@
-use crate::token_type::TokenType;
+use crate::{bigint::BigInt, token_type::TokenType};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone)]
 pub enum Literal {
     Isize(isize),
+    // only for integers outside the range of `isize`
+    BigInt(BigInt),
     String(String),
     Float(f64),
     Bool(bool),
@@ -15,6 +17,7 @@ impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Literal::Isize(i) => write!(f, "{}", i),
+            Literal::BigInt(i) => write!(f, "{}", i),
             Literal::String(string) => write!(f, "{}", string),
             Literal::Float(float) => write!(f, "{}", float),
             Literal::Bool(boolean) => write!(f, "{}", boolean),
@@ -30,6 +33,7 @@ impl PartialEq for Literal {
         match (self, other) {
             (Literal::Bool(a), Literal::Bool(b)) => a.eq(b),
             (Literal::Isize(a), Literal::Isize(b)) => a.eq(b),
+            (Literal::BigInt(a), Literal::BigInt(b)) => a.eq(b),
             (Literal::String(a), Literal::String(b)) => a.eq(b),
             (Literal::Float(a), Literal::Float(b)) => a.eq(b),
             (Literal::None, Literal::None) => true,
@@ -42,6 +46,7 @@ impl Hash for Literal {
     fn hash<H: Hasher>(&self, state: &mut H) {
         match self {
             Literal::Isize(i) => i.hash(state),
+            Literal::BigInt(i) => i.hash(state),
             Literal::String(s) => s.hash(state),
             Literal::Float(f) => f.to_bits().hash(state),
             Literal::Bool(b) => b.hash(state),
@@ -50,25 +55,93 @@ impl Hash for Literal {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+// Identifies a source file registered by the embedder.
+pub type FileId = usize;
+
+// A range of source text. Offsets are in bytes, lines and columns start at 1
+// and the end position is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub file: Option<FileId>,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    // The smallest span covering both `self` and `other`.
+    pub fn merge(self, other: Span) -> Span {
+        if other == Span::default() {
+            return self;
+        }
+        if self == Span::default() {
+            return other;
+        }
+        let first = if self.start <= other.start {
+            self
+        } else {
+            other
+        };
+        let last = if self.end >= other.end { self } else { other };
+        Span {
+            file: first.file,
+            start: first.start,
+            end: last.end,
+            line: first.line,
+            column: first.column,
+            end_line: last.end_line,
+            end_column: last.end_column,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub literal: Literal,
-    pub line: usize,
+    pub span: Span,
+    // the `///` comment lines right before the token, if any
+    pub doc: Option<String>,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: Literal, line: usize) -> Token {
+    pub fn new(token_type: TokenType, lexeme: String, literal: Literal, span: Span) -> Token {
         Token {
             token_type,
             lexeme,
             literal,
-            line,
+            span,
+            doc: None,
         }
     }
 }
 
+// The doc comment isn't part of a token's identity, so it's left out of
+// equality and hashing.
+impl Eq for Token {}
+
+impl PartialEq for Token {
+    fn eq(&self, other: &Token) -> bool {
+        self.token_type == other.token_type
+            && self.lexeme == other.lexeme
+            && self.literal == other.literal
+            && self.span == other.span
+    }
+}
+
+impl Hash for Token {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.token_type.hash(state);
+        self.lexeme.hash(state);
+        self.literal.hash(state);
+        self.span.hash(state);
+    }
+}
+
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -78,3 +151,31 @@ impl fmt::Display for Token {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash(token: &Token) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        token.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn doc_comments_do_not_affect_equality_or_hashing() {
+        let token = Token::new(
+            TokenType::IDENTIFIER,
+            "f".into(),
+            Literal::None,
+            Span::default(),
+        );
+        let documented = Token {
+            doc: Some(String::from("Does things.")),
+            ..token.clone()
+        };
+        assert_eq!(token, documented);
+        assert_eq!(hash(&token), hash(&documented));
+    }
+}
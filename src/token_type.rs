@@ -1,35 +1,62 @@
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-
 pub enum TokenType {
     LEFTPAREN,
     RIGHTPAREN,
     LEFTBRACE,
     RIGHTBRACE,
+    LEFTBRACKET,
+    RIGHTBRACKET,
     COMMA,
     DOT,
     MINUS,
     PLUS,
     SEMICOLON,
+    COLON,
     SLASH,
     STAR,
+    PERCENT,
+    STARSTAR,
+    // `~/`, division rounding toward negative infinity
+    TILDESLASH,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    TILDE,
+    LESSLESS,
+    GREATERGREATER,
+    PLUSEQUAL,
+    MINUSEQUAL,
+    STAREQUAL,
+    SLASHEQUAL,
+    PLUSPLUS,
+    MINUSMINUS,
     BANG,
     BANGEQUAL,
     EQUAL,
     EQUALEQUAL,
+    // `=>`, between the parameters and the body of an arrow function
+    ARROW,
     GREATER,
     GREATEREQUAL,
     LESS,
     LESSEQUAL,
     IDENTIFIER,
     STRING,
+    // the parts of an interpolated string before a `${` and after the last `}`
+    INTERPOLATION,
+    INTERPOLATIONEND,
     NUMBER,
     AND,
+    BREAK,
     CLASS,
+    CONTINUE,
     ELSE,
     FALSE,
     FUN,
     FOR,
     IF,
+    IN,
     NIL,
     OR,
     PRINT,
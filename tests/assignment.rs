@@ -0,0 +1,76 @@
+mod common;
+
+use common::{errors, output};
+
+#[test]
+fn assigning_an_outer_local_is_seen_by_closures() {
+    let source = r#"
+        {
+            var n = 0;
+            fun show() { print n; }
+            {
+                n = 1;
+                {
+                    n = n + 1;
+                }
+            }
+            show();
+            print n;
+        }
+    "#;
+    assert_eq!(output(source), "2\n2\n");
+}
+
+#[test]
+fn compound_assignment_to_variables() {
+    assert_eq!(
+        output("var x = 5; x += 2; print x; x -= 1; print x; x *= 3; print x; x /= 2; print x;"),
+        "7\n6\n18\n9\n"
+    );
+    assert_eq!(output("var s = \"a\"; s += \"b\"; print s;"), "ab\n");
+}
+
+#[test]
+fn increment_and_decrement() {
+    assert_eq!(
+        output("var x = 1; print x++; print x; print ++x; print x--; print --x;"),
+        "1\n2\n3\n3\n1\n"
+    );
+}
+
+#[test]
+fn compound_assignment_to_properties_and_elements() {
+    assert_eq!(
+        output("class P {} var p = P(); p.n = 1; p.n += 4; print p.n++; print p.n; print --p.n;"),
+        "5\n6\n5\n"
+    );
+    assert_eq!(
+        output("var xs = [1, 2]; xs[0] += 10; xs[1]++; print xs;"),
+        "[11, 3]\n"
+    );
+}
+
+#[test]
+fn the_target_object_is_evaluated_once() {
+    let source = r#"
+        class Counter { init() { this.count = 0; } }
+        var counter = Counter();
+        fun get() { print "get"; return counter; }
+        get().count += 1;
+        get().count++;
+        print counter.count;
+    "#;
+    assert_eq!(output(source), "get\nget\n2\n");
+}
+
+#[test]
+fn compound_assignment_errors() {
+    assert_eq!(
+        errors("1++;"),
+        "[line 1:2] Error at '++': Invalid assignment target.\n"
+    );
+    assert_eq!(
+        errors("var x = nil; x += 1;"),
+        "Operands must be two numbers or two strings.\n[line 1:16]\n"
+    );
+}
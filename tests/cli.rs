@@ -0,0 +1,123 @@
+// Runs the `rust_lox` binary and checks its output and exit codes.
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn lox(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust_lox"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn runs_code_from_the_command_line() {
+    let output = lox(&["-e", "print 1 + 2;"], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "3\n");
+}
+
+#[test]
+fn runs_a_script_file() {
+    let path = std::env::temp_dir().join(format!("cli-test-{}.lox", std::process::id()));
+    std::fs::write(&path, "print \"from a file\";").unwrap();
+    let output = lox(&[path.to_str().unwrap()], "");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "from a file\n");
+}
+
+#[test]
+fn reads_the_program_from_stdin() {
+    let output = lox(&["-"], "print \"piped\";");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "piped\n");
+}
+
+#[test]
+fn passes_the_remaining_arguments_to_the_script() {
+    let output = lox(
+        &[
+            "-e",
+            "print argc; print arg(0); print arg(1); print arg(2);",
+            "a",
+            "b",
+        ],
+        "",
+    );
+    assert_eq!(stdout(&output), "2\na\nb\nnil\n");
+}
+
+#[test]
+fn the_prompt_keeps_state_and_survives_errors() {
+    let output = lox(&[], "var a = 1;\nprint nope;\nprint a + 1;\n");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "> > > 2\n> ");
+    assert!(stderr(&output).contains("Undefined variable"));
+}
+
+#[test]
+fn color_can_be_forced_on_or_off() {
+    let colored = |args: &[&str]| stderr(&lox(args, "")).contains('\x1b');
+    assert!(colored(&["--color=always", "-e", "print nope;"]));
+    assert!(colored(&["--color", "-e", "print nope;"]));
+    assert!(!colored(&["--color=never", "-e", "print nope;"]));
+    // piped stderr is not a terminal
+    assert!(!colored(&["--color=auto", "-e", "print nope;"]));
+    assert!(!colored(&["-e", "print nope;"]));
+}
+
+#[test]
+fn exits_with_64_on_usage_errors() {
+    assert_eq!(lox(&["-e"], "").status.code(), Some(64));
+    assert_eq!(lox(&["-x"], "").status.code(), Some(64));
+    let output = lox(&["--color=sometimes", "-e", "print 1;"], "");
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr(&output).contains("Usage:"));
+}
+
+#[test]
+fn exits_with_65_on_compile_errors() {
+    let output = lox(&["--error-format=short", "-e", "print ;"], "");
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        stderr(&output),
+        "[line 1:7] Error at ';': Expect expression.\n"
+    );
+    assert_eq!(lox(&["-e", "return 1;"], "").status.code(), Some(65));
+}
+
+#[test]
+fn exits_with_66_when_the_script_cannot_be_read() {
+    let output = lox(&["no/such/script.lox"], "");
+    assert_eq!(output.status.code(), Some(66));
+    assert!(stderr(&output).contains("Could not read 'no/such/script.lox'"));
+}
+
+#[test]
+fn exits_with_70_on_runtime_errors() {
+    let output = lox(&["-e", "print 1; print 1 / 0; print 2;"], "");
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(stdout(&output), "1\n");
+    assert!(stderr(&output).contains("Integer division by zero."));
+}
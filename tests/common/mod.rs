@@ -0,0 +1,48 @@
+// Helpers shared by the script tests: each runs a program in a fresh session
+// and captures what it prints.
+#![allow(dead_code)]
+
+use rust_lox::{diagnostic::ErrorFormat, output::Buffer, Lox};
+
+// What the program printed. Fails the test if the program reports an error.
+pub fn output(source: &str) -> String {
+    let (result, output, diagnostics) = run(source);
+    if result.is_err() {
+        panic!("unexpected error:\n{}", diagnostics);
+    }
+    output
+}
+
+// The error messages of a program that is expected to fail, one per line.
+pub fn errors(source: &str) -> String {
+    let (result, _, diagnostics) = run(source);
+    if result.is_ok() {
+        panic!("expected an error from:\n{}", source);
+    }
+    diagnostics
+}
+
+// The result of running the program, what it printed and its error messages.
+pub fn run(source: &str) -> (rust_lox::error::Result<()>, String, String) {
+    let output = Buffer::new();
+    let diagnostics = Buffer::new();
+    let mut lox = Lox::new();
+    lox.set_output(output.clone());
+    lox.set_diagnostics(diagnostics.clone());
+    lox.set_error_format(ErrorFormat::Short);
+    let result = lox.run_source(source);
+    (result, output.contents(), diagnostics.contents())
+}
+
+// The error messages of a program named `test.lox`, written in `format`.
+pub fn diagnostics(source: &str, format: ErrorFormat) -> String {
+    let diagnostics = Buffer::new();
+    let mut lox = Lox::new();
+    lox.set_output(Buffer::new());
+    lox.set_diagnostics(diagnostics.clone());
+    lox.set_error_format(format);
+    if lox.run_named_source("test.lox", source).is_ok() {
+        panic!("expected an error from:\n{}", source);
+    }
+    diagnostics.contents()
+}
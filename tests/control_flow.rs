@@ -0,0 +1,68 @@
+mod common;
+
+use common::{errors, output};
+
+#[test]
+fn if_runs_exactly_one_branch() {
+    assert_eq!(output("if (true) print 1; else print 2;"), "1\n");
+    assert_eq!(output("if (false) print 1; else print 2;"), "2\n");
+    assert_eq!(output("if (nil) print 1;"), "");
+    assert_eq!(
+        output("if (0) print \"zero is truthy\"; else print \"no\";"),
+        "zero is truthy\n"
+    );
+}
+
+#[test]
+fn break_and_continue_in_the_innermost_loop() {
+    assert_eq!(
+        output("for (var i = 0; i < 5; i++) { if (i == 1) continue; if (i == 3) break; print i; }"),
+        "0\n2\n"
+    );
+    assert_eq!(
+        output("var i = 0; while (i < 3) { i++; if (i == 2) continue; print i; }"),
+        "1\n3\n"
+    );
+}
+
+#[test]
+fn labels_name_an_outer_loop() {
+    let source = r#"
+        outer: for (var i = 0; i < 3; i++) {
+            for (var j = 0; j < 3; j++) {
+                if (j == 1) continue outer;
+                if (i == 2) break outer;
+                print i * 10 + j;
+            }
+        }
+    "#;
+    assert_eq!(output(source), "0\n10\n");
+}
+
+#[test]
+fn jumps_outside_loops_are_resolve_errors() {
+    assert_eq!(
+        errors("break;"),
+        "[line 1:1] Error at 'break': Cannot use 'break' outside of a loop.\n"
+    );
+    assert_eq!(
+        errors("continue;"),
+        "[line 1:1] Error at 'continue': Cannot use 'continue' outside of a loop.\n"
+    );
+    assert_eq!(
+        errors("while (true) { fun f() { break; } }"),
+        "[line 1:26] Error at 'break': Cannot use 'break' outside of a loop.\n"
+    );
+}
+
+#[test]
+fn labels_must_name_an_enclosing_loop() {
+    assert_eq!(
+        errors("outer: while (true) { break inner; }"),
+        "[line 1:29] Error at 'inner': No enclosing loop is labeled 'inner'.\n"
+    );
+    assert_eq!(
+        errors("a: while (false) {} while (true) { break a; }"),
+        "[line 1:42] Error at 'a': No enclosing loop is labeled 'a'.\n"
+    );
+}
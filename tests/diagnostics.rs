@@ -0,0 +1,99 @@
+mod common;
+
+use common::diagnostics;
+use rust_lox::diagnostic::ErrorFormat;
+
+#[test]
+fn underlines_the_span_in_the_source_line() {
+    assert_eq!(
+        diagnostics("var a = 1;\nprint a +;\n", ErrorFormat::Human),
+        "\
+error[parse]: Expect expression.
+ --> test.lox:2:10
+  |
+2 | print a +;
+  |          ^
+"
+    );
+}
+
+#[test]
+fn names_the_phase_that_found_the_error() {
+    assert_eq!(
+        diagnostics("print 1 + nil;", ErrorFormat::Human),
+        "\
+error[runtime]: Operands must be two numbers or two strings.
+ --> test.lox:1:9
+  |
+1 | print 1 + nil;
+  |         ^
+"
+    );
+}
+
+#[test]
+fn shows_notes_with_their_own_snippet() {
+    assert_eq!(
+        diagnostics("{\n  var a = 1;\n  var a = 2;\n}\n", ErrorFormat::Human),
+        "\
+error[resolve]: Variable with this name already declared in this scope.
+ --> test.lox:3:7
+  |
+3 |   var a = 2;
+  |       ^
+note: previously declared here
+ --> test.lox:2:7
+  |
+2 |   var a = 1;
+  |       ^
+"
+    );
+}
+
+#[test]
+fn json_has_one_object_per_error() {
+    assert_eq!(
+        diagnostics("print 1 + nil;", ErrorFormat::Json),
+        "{\"severity\":\"error\",\"phase\":\"runtime\",\
+\"message\":\"Operands must be two numbers or two strings.\",\
+\"file\":\"test.lox\",\"line\":1,\"column\":9,\
+\"span\":{\"start\":8,\"end\":9,\"line\":1,\"column\":9,\"end_line\":1,\"end_column\":10},\
+\"notes\":[]}\n"
+    );
+}
+
+#[test]
+fn json_includes_notes() {
+    let json = diagnostics("{\n  var a = 1;\n  var a = 2;\n}\n", ErrorFormat::Json);
+    assert!(json.contains(
+        "\"notes\":[{\"message\":\"previously declared here\",\
+\"file\":\"test.lox\",\"line\":2,\"column\":7,"
+    ));
+}
+
+#[test]
+fn json_reports_every_syntax_error() {
+    let json = diagnostics("print +;\nprint -;\n", ErrorFormat::Json);
+    assert_eq!(json.lines().count(), 2);
+    assert!(json
+        .lines()
+        .all(|line| line.starts_with('{') && line.ends_with('}')));
+}
+
+#[test]
+fn carets_line_up_after_wide_characters_and_tabs() {
+    assert_eq!(
+        diagnostics("var 变量 = \"ü\" + nil;", ErrorFormat::Human),
+        "\
+error[runtime]: Operands must be two numbers or two strings.
+ --> test.lox:1:14
+  |
+1 | var 变量 = \"ü\" + nil;
+  |                ^
+"
+    );
+    assert!(
+        diagnostics("var x = 1;\tprint x + nil;", ErrorFormat::Human)
+            .ends_with("  |           \t        ^\n")
+    );
+}
@@ -0,0 +1,52 @@
+use rust_lox::{object::Object, output::Buffer, token::Literal, Lox};
+
+fn session() -> (Lox, Buffer) {
+    let output = Buffer::new();
+    let mut lox = Lox::new();
+    lox.set_output(output.clone());
+    lox.set_diagnostics(Buffer::new());
+    (lox, output)
+}
+
+#[test]
+fn globals_stay_visible_between_runs() {
+    let (mut lox, output) = session();
+    lox.run_source("var greeting = \"hi\"; fun twice(x) { return x * 2; }")
+        .unwrap();
+    lox.run_source("print greeting; print twice(21);").unwrap();
+    assert_eq!(output.contents(), "hi\n42\n");
+}
+
+#[test]
+fn reads_globals_set_by_a_script() {
+    let (mut lox, _) = session();
+    lox.run_source("var port = 8000 + 80; var name = \"lox\";")
+        .unwrap();
+    assert!(matches!(
+        lox.global("port"),
+        Some(Object::Literal(Literal::Isize(8080)))
+    ));
+    assert!(matches!(
+        lox.global("name"),
+        Some(Object::Literal(Literal::String(name))) if name == "lox"
+    ));
+    assert!(lox.global("missing").is_none());
+}
+
+#[test]
+fn evaluates_expressions() {
+    let (mut lox, _) = session();
+    lox.run_source("var config = 40;").unwrap();
+    assert_eq!(lox.eval_expr("config + 2").unwrap().to_string(), "42");
+    assert_eq!(lox.eval_expr("\"a\" + \"b\"").unwrap().to_string(), "ab");
+    assert!(lox.eval_expr("1 +").is_err());
+    assert!(lox.eval_expr("nope").is_err());
+}
+
+#[test]
+fn errors_leave_the_session_usable() {
+    let (mut lox, output) = session();
+    assert!(lox.run_source("var a = 1; print a / 0;").is_err());
+    lox.run_source("print a;").unwrap();
+    assert_eq!(output.contents(), "1\n");
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::{errors, run};
+
+#[test]
+fn reports_every_syntax_error() {
+    assert_eq!(
+        errors("print (1 + ;\nvar = 2;\nprint 3;"),
+        "[line 1:12] Error at ';': Expect expression.\n\
+         [line 2:5] Error at '=': Expect variable name.\n"
+    );
+}
+
+#[test]
+fn resolves_what_could_be_parsed_despite_syntax_errors() {
+    assert_eq!(
+        errors("print (1 + ;\n{ var a = a; }\nprint 2 +;"),
+        "[line 1:12] Error at ';': Expect expression.\n\
+         [line 2:11] Error at 'a': Cannot read local variable in its own initializer.\n\
+         [line 3:10] Error at ';': Expect expression.\n"
+    );
+}
+
+#[test]
+fn nothing_runs_when_there_are_errors() {
+    let (result, output, diagnostics) = run("print 1;\nreturn 2;");
+    assert!(result.is_err());
+    assert_eq!(output, "");
+    assert_eq!(
+        diagnostics,
+        "[line 2:1] Error at 'return': Cannot return from top-level code.\n"
+    );
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{errors, output};
+
+#[test]
+fn iterates_over_lists_maps_strings_and_ranges() {
+    assert_eq!(
+        output("for (var x in [1, \"a\", nil]) print x;"),
+        "1\na\nnil\n"
+    );
+    assert_eq!(
+        output("for (var k in {\"b\": 1, \"a\": 2}) print k;"),
+        "b\na\n"
+    );
+    assert_eq!(output("for (var c in \"hé!\") print c;"), "h\né\n!\n");
+    assert_eq!(output("for (var i in Range(3)) print i;"), "0\n1\n2\n");
+    assert_eq!(
+        output("for (var i in Range(5, 0, -2)) print i;"),
+        "5\n3\n1\n"
+    );
+}
+
+#[test]
+fn the_loop_variable_is_fresh_on_every_pass() {
+    let source = r#"
+        var fs = [];
+        for (var i in [1, 2]) fs.push(fun () { return i; });
+        print fs[0]() + fs[1]();
+    "#;
+    assert_eq!(output(source), "3\n");
+}
+
+#[test]
+fn iterates_over_instances_with_an_iter_method() {
+    let source = r#"
+        class Countdown {
+            init(n) { this.n = n; }
+            iter() { return this; }
+            next() {
+                if (this.n == 0) return nil;
+                this.n = this.n - 1;
+                return this.n + 1;
+            }
+        }
+        for (var i in Countdown(3)) print i;
+    "#;
+    assert_eq!(output(source), "3\n2\n1\n");
+}
+
+#[test]
+fn continue_and_break_with_labels_cross_nested_loops() {
+    let source = r#"
+        outer: for (var i in Range(3)) {
+            for (var j in Range(3)) {
+                if (j > i) continue outer;
+                if (i == 2) break outer;
+                print i * 10 + j;
+            }
+        }
+        print "done";
+    "#;
+    assert_eq!(output(source), "0\n10\n11\ndone\n");
+}
+
+#[test]
+fn rejects_values_that_cannot_be_iterated() {
+    assert!(errors("for (var x in 1) print x;").contains(
+        "Can only iterate over lists, maps, strings, ranges and instances with an 'iter' method."
+    ));
+    // only methods count, not fields
+    assert!(errors(
+        "class A {} var a = A(); a.iter = fun () { return a; }; for (var x in a) print x;"
+    )
+    .contains("instances with an 'iter' method."));
+}
+
+#[test]
+fn iter_must_return_an_instance_with_a_next_method() {
+    let message = "'iter()' must return an instance with a 'next' method.";
+    assert!(
+        errors("class A { iter() { return 1; } } for (var x in A()) print x;").contains(message)
+    );
+    assert!(
+        errors("class B {} class A { iter() { return B(); } } for (var x in A()) print x;")
+            .contains(message)
+    );
+}
+
+#[test]
+fn empty_ranges_and_bad_bounds() {
+    assert_eq!(output("for (var i in Range(3, 3)) print i;"), "");
+    assert_eq!(
+        errors("Range(0, 1, 0);"),
+        "Range step cannot be zero.\n[line 1:14]\n"
+    );
+    assert_eq!(
+        errors("Range(1.5);"),
+        "Range bounds must be integers.\n[line 1:10]\n"
+    );
+    assert_eq!(
+        errors("Range();"),
+        "Expected 1 to 3 arguments but got 0.\n[line 1:7]\n"
+    );
+}
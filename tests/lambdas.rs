@@ -0,0 +1,51 @@
+mod common;
+
+use common::output;
+
+#[test]
+fn anonymous_functions_are_expressions() {
+    assert_eq!(
+        output("var f = fun (a, b) { return a * b; }; print f(6, 7);"),
+        "42\n"
+    );
+    assert_eq!(output("print fun () {};"), "<fn lambda>\n");
+    assert_eq!(output("print (fun (x) { return x + 1; })(1);"), "2\n");
+}
+
+#[test]
+fn arrow_functions_return_their_body() {
+    assert_eq!(output("var sq = (x) => x * x; print sq(9);"), "81\n");
+    assert_eq!(output("var k = () => 7; print k();"), "7\n");
+    assert_eq!(output("print [1, 2, 3].map((x) => x * 2);"), "[2, 4, 6]\n");
+}
+
+#[test]
+fn lambdas_close_over_the_scope_they_are_created_in() {
+    let source = r#"
+        fun counter() {
+            var n = 0;
+            return () => n += 1;
+        }
+        var a = counter();
+        var b = counter();
+        a(); a();
+        print a();
+        print b();
+    "#;
+    assert_eq!(output(source), "3\n1\n");
+}
+
+#[test]
+fn identical_lambdas_in_different_scopes_resolve_separately() {
+    let source = r#"
+        var x = "global";
+        var f = fun () { return x; };
+        {
+            var x = "local";
+            var g = fun () { return x; };
+            print f();
+            print g();
+        }
+    "#;
+    assert_eq!(output(source), "global\nlocal\n");
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::{errors, output};
+
+#[test]
+fn a_list_that_contains_itself_prints_an_ellipsis() {
+    assert_eq!(output("var a = [1]; a.push(a); print a;"), "[1, [...]]\n");
+}
+
+#[test]
+fn literals_and_indexing() {
+    assert_eq!(
+        output(
+            "var xs = [1, \"a\", nil, [2],]; print xs; print xs.len(); print xs[1]; print xs[-1];"
+        ),
+        "[1, \"a\", nil, [2]]\n4\na\n[2]\n"
+    );
+    assert_eq!(
+        output("var xs = [1, 2]; xs[0] = 9; xs[-1] = 8; print xs;"),
+        "[9, 8]\n"
+    );
+}
+
+#[test]
+fn strings_are_escaped_so_they_read_back() {
+    // printing the literal gives back the same literal
+    let list = r#"["a\"b", "x\ny", "t\tb\\", "\u{1}", "\${x}", "$"]"#;
+    assert_eq!(output(&format!("print {};", list)), format!("{}\n", list));
+}
+
+#[test]
+fn slices_clamp_their_bounds() {
+    let source = r#"
+        var xs = [0, 1, 2, 3, 4];
+        print xs[1:3];
+        print xs[:2];
+        print xs[3:];
+        print xs[-2:];
+        print xs[4:1];
+        print xs[-100:100];
+    "#;
+    assert_eq!(
+        output(source),
+        "[1, 2]\n[0, 1]\n[3, 4]\n[3, 4]\n[]\n[0, 1, 2, 3, 4]\n"
+    );
+}
+
+#[test]
+fn lists_are_shared_not_copied() {
+    assert_eq!(
+        output("var a = [1]; var b = a; b.push(2); print a; print a == b; print [1] == [1];"),
+        "[1, 2]\ntrue\nfalse\n"
+    );
+    assert_eq!(
+        output("var xs = [0, 1]; var ys = xs[:]; ys.push(2); print xs;"),
+        "[0, 1]\n"
+    );
+}
+
+#[test]
+fn methods() {
+    let source = r#"
+        var xs = [];
+        xs.push(3); xs.push(1); xs.push(2);
+        print xs.pop();
+        print xs;
+        print [1, 2, 3, 4].map((x) => x * x);
+        print [1, 2, 3, 4].filter((x) => x % 2 == 0);
+        var ys = [3, 1, 2];
+        ys.sort();
+        print ys;
+        ys.sort((a, b) => b - a);
+        print ys;
+        var words = ["b", "c", "a"];
+        words.sort();
+        print words;
+    "#;
+    assert_eq!(
+        output(source),
+        "2\n[3, 1]\n[1, 4, 9, 16]\n[2, 4]\n[1, 2, 3]\n[3, 2, 1]\n[\"a\", \"b\", \"c\"]\n"
+    );
+}
+
+#[test]
+fn index_errors_point_at_the_brackets() {
+    assert_eq!(
+        errors("print [1][1];"),
+        "Index 1 is out of bounds for a list of length 1.\n[line 1:12]\n"
+    );
+    assert_eq!(
+        errors("print [1][\"0\"];"),
+        "List index must be an integer.\n[line 1:14]\n"
+    );
+    assert_eq!(
+        errors("print 1[0];"),
+        "Only lists and maps can be indexed.\n[line 1:10]\n"
+    );
+    assert_eq!(
+        errors("print [1][\"a\":];"),
+        "Slice bounds must be integers.\n[line 1:15]\n"
+    );
+}
+
+#[test]
+fn method_errors() {
+    assert_eq!(
+        errors("print [].pop();"),
+        "Cannot pop from an empty list.\n[line 1:14]\n"
+    );
+    assert_eq!(
+        errors("[1, nil].sort();"),
+        "Only lists of numbers or of strings can be sorted without a comparator.\n[line 1:15]\n"
+    );
+    assert_eq!(
+        errors("[1, 2].sort((a, b) => \"x\");"),
+        "Comparator must return a number.\n[line 1:26]\n"
+    );
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::{errors, output};
+
+#[test]
+fn a_map_that_contains_itself_prints_an_ellipsis() {
+    assert_eq!(
+        output("var m = {}; m[\"x\"] = m; print m;"),
+        "{\"x\": {...}}\n"
+    );
+}
+
+#[test]
+fn literals_keep_insertion_order() {
+    assert_eq!(
+        output("var m = {\"a\": 1, 2: \"two\", nil: true,}; print m; print m[\"a\"]; print m[2];"),
+        "{\"a\": 1, 2: \"two\", nil: true}\n1\ntwo\n"
+    );
+    assert_eq!(
+        output(r#"print {"k\"": "v\n"};"#),
+        "{\"k\\\"\": \"v\\n\"}\n"
+    );
+}
+
+#[test]
+fn methods() {
+    let source = r#"
+        var m = {"a": 1, "b": 2};
+        m["c"] = 3;
+        print m.len();
+        print m.keys();
+        print m.values();
+        print m.has("b");
+        print m.remove("b");
+        print m.remove("z");
+        print m.has("b");
+        print m;
+    "#;
+    assert_eq!(
+        output(source),
+        "3\n[\"a\", \"b\", \"c\"]\n[1, 2, 3]\ntrue\n2\nnil\nfalse\n{\"a\": 1, \"c\": 3}\n"
+    );
+}
+
+#[test]
+fn whole_floats_are_the_same_key_as_integers() {
+    assert_eq!(
+        output("var m = {1: \"int\"}; print m[1.0]; m[2.0] = \"x\"; print m; print m.has(2);"),
+        "int\n{1: \"int\", 2: \"x\"}\ntrue\n"
+    );
+    assert_eq!(
+        output("var m = {}; m[100000000000000000000] = 1; print m.has(1e20); print m[-2e20] = 2; print m.has(-200000000000000000000); print m;"),
+        "true\n2\ntrue\n{100000000000000000000: 1, -200000000000000000000: 2}\n"
+    );
+}
+
+#[test]
+fn maps_are_shared_not_copied() {
+    assert_eq!(
+        output("var m = {}; var n = m; n[\"k\"] = 1; print m; print {} == {};"),
+        "{\"k\": 1}\nfalse\n"
+    );
+}
+
+#[test]
+fn key_errors() {
+    assert_eq!(
+        errors("print {\"a\": 1}[\"b\"];"),
+        "Key \"b\" is not in the map.\n[line 1:19]\n"
+    );
+    assert_eq!(
+        errors("print {[1]: 2};"),
+        "Map keys must be numbers, strings, booleans or nil.\n[line 1:14]\n"
+    );
+    assert_eq!(
+        errors("var m = {}; m[0/0.0] = 1;"),
+        "Map keys cannot be NaN.\n[line 1:20]\n"
+    );
+}
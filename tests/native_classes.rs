@@ -0,0 +1,163 @@
+mod common;
+
+use std::{cell::Cell, rc::Rc};
+
+use common::{errors, output};
+use rust_lox::{
+    callable::Arity, error::Error, lox_class::LoxClass, object::Object, output::Buffer,
+    token::Literal, Lox,
+};
+
+// A host class whose instances hold a name, with a native `greet` method.
+fn greeter() -> LoxClass {
+    LoxClass::native("Greeter")
+        .constructor(Arity::Fixed(1), |_, arguments| {
+            Ok(Rc::new(arguments[0].to_string()))
+        })
+        .method("greet", Arity::Fixed(0), |_, instance, _| {
+            let name = instance.data::<String>().unwrap();
+            Ok(Object::Literal(Literal::String(format!("hello {}", name))))
+        })
+}
+
+fn run(source: &str) -> String {
+    let output = Buffer::new();
+    let mut lox = Lox::new();
+    lox.set_output(output.clone());
+    lox.define_class(greeter());
+    lox.run_source(source).unwrap();
+    output.contents()
+}
+
+// A counter whose `value` property is served by the getter hook and can only
+// be set to integers.
+fn counter() -> LoxClass {
+    LoxClass::native("Counter")
+        .constructor(Arity::Fixed(0), |_, _| Ok(Rc::new(Cell::new(0isize))))
+        .method("increment", Arity::Fixed(0), |_, instance, _| {
+            let count = instance.data::<Cell<isize>>().unwrap();
+            count.set(count.get() + 1);
+            Ok(Object::Literal(Literal::None))
+        })
+        .getter(|instance, name| match name {
+            "value" => {
+                let count = instance.data::<Cell<isize>>().unwrap().get();
+                Ok(Some(Object::Literal(Literal::Isize(count))))
+            }
+            _ => Ok(None),
+        })
+        .setter(|instance, name, value| match (name, value) {
+            ("value", Object::Literal(Literal::Isize(i))) => {
+                instance.data::<Cell<isize>>().unwrap().set(*i);
+                Ok(true)
+            }
+            ("value", _) => Err(Error::NativeError(String::from(
+                "value must be an integer.",
+            ))),
+            _ => Ok(false),
+        })
+}
+
+#[test]
+fn host_classes_keep_data_and_hook_properties() {
+    let output = Buffer::new();
+    let mut lox = Lox::new();
+    lox.set_output(output.clone());
+    lox.set_diagnostics(Buffer::new());
+    lox.define_class(counter());
+    let source = r#"
+        var c = Counter();
+        c.increment();
+        c.increment();
+        print c.value;
+        c.value = 10;
+        c.increment();
+        print c.value;
+        c.label = "plain field";
+        print c.label;
+        print Counter;
+    "#;
+    lox.run_source(source).unwrap();
+    assert_eq!(output.contents(), "2\n11\nplain field\nCounter\n");
+    assert!(lox.run_source("c.value = \"x\";").is_err());
+}
+
+#[test]
+fn subclasses_inherit_the_host_constructor_and_methods() {
+    let source = r#"
+        class Polite < Greeter {
+            bye() { return "bye"; }
+        }
+        var p = Polite("you");
+        print p.greet();
+        print p.bye();
+    "#;
+    assert_eq!(run(source), "hello you\nbye\n");
+}
+
+#[test]
+fn super_reaches_host_methods() {
+    let source = r#"
+        class Loud < Greeter {
+            greet() { return super.greet() + "!"; }
+        }
+        print Loud("lox").greet();
+    "#;
+    assert_eq!(run(source), "hello lox!\n");
+}
+
+#[test]
+fn super_init_reaches_the_host_constructor() {
+    let source = r#"
+        class Named < Greeter {
+            init(name) {
+                super.init(name);
+                this.name = name;
+            }
+        }
+        var n = Named("lox");
+        print n.greet();
+        print n.name;
+    "#;
+    assert_eq!(run(source), "hello lox\nlox\n");
+
+    let source = r#"
+        class B < Range { init(n) { super.init(n); } }
+        for (var i in B(2)) print i;
+    "#;
+    assert_eq!(output(source), "0\n1\n");
+}
+
+#[test]
+fn super_init_runs_the_host_constructor_with_its_own_arguments() {
+    let source = r#"
+        class Anonymous < Greeter {
+            init() { super.init("nobody"); }
+        }
+        print Anonymous().greet();
+    "#;
+    assert_eq!(run(source), "hello nobody\n");
+
+    let source = r#"
+        class B < Range { init(a, b) { super.init(b - a); } }
+        for (var i in B(5, 7)) print i;
+    "#;
+    assert_eq!(output(source), "0\n1\n");
+
+    assert_eq!(
+        errors("class B < Range { init() { super.init(1); super.init(2); } } B();"),
+        "B instance was already initialized.\n[line 1:55]\n"
+    );
+    assert_eq!(
+        errors("class B < Range { init() { super.init(); } } B();"),
+        "Expected 1 to 3 arguments but got 0.\n[line 1:39]\n"
+    );
+}
+
+#[test]
+fn super_reports_missing_properties() {
+    assert!(
+        errors("class A {} class B < A { f() { return super.g; } } B().f();")
+            .contains("Undefined property 'g'")
+    );
+}
@@ -0,0 +1,76 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rust_lox::{
+    callable::Arity, diagnostic::ErrorFormat, error::Error, object::Object, output::Buffer,
+    token::Literal, Lox,
+};
+
+fn session() -> (Lox, Buffer, Buffer) {
+    let output = Buffer::new();
+    let diagnostics = Buffer::new();
+    let mut lox = Lox::new();
+    lox.set_output(output.clone());
+    lox.set_diagnostics(diagnostics.clone());
+    lox.set_error_format(ErrorFormat::Short);
+    (lox, output, diagnostics)
+}
+
+#[test]
+fn scripts_call_host_functions() {
+    let (mut lox, output, _) = session();
+    lox.define_native("sum", Arity::Variadic, |_, arguments| {
+        let mut total = 0;
+        for argument in arguments {
+            match argument {
+                Object::Literal(Literal::Isize(i)) => total += i,
+                _ => return Err(Error::NativeError(String::from("Expected integers."))),
+            }
+        }
+        Ok(Object::Literal(Literal::Isize(total)))
+    });
+    lox.run_source("print sum(); print sum(1, 2, 3); print sum;")
+        .unwrap();
+    assert_eq!(output.contents(), "0\n6\n<native fn sum>\n");
+}
+
+#[test]
+fn host_functions_can_keep_state() {
+    let (mut lox, output, _) = session();
+    let calls = Rc::new(RefCell::new(vec![]));
+    let log = Rc::clone(&calls);
+    lox.define_native("log", Arity::Fixed(1), move |_, arguments| {
+        log.borrow_mut().push(arguments[0].to_string());
+        Ok(Object::Literal(Literal::None))
+    });
+    lox.run_source("log(\"a\"); log(1 + 1);").unwrap();
+    assert_eq!(*calls.borrow(), vec!["a", "2"]);
+    assert_eq!(output.contents(), "");
+}
+
+#[test]
+fn native_errors_are_reported_at_the_call_site() {
+    let (mut lox, _, diagnostics) = session();
+    lox.define_native("fail", Arity::Fixed(0), |_, _| {
+        Err(Error::NativeError(String::from("Something broke.")))
+    });
+    assert!(lox.run_source("print 1;\n  fail();").is_err());
+    assert_eq!(diagnostics.contents(), "Something broke.\n[line 2:8]\n");
+}
+
+#[test]
+fn arity_is_checked_before_calling() {
+    let (mut lox, _, diagnostics) = session();
+    lox.define_native("one", Arity::Fixed(1), |_, _| unreachable!());
+    assert!(lox.run_source("one(1, 2);").is_err());
+    assert_eq!(
+        diagnostics.contents(),
+        "Expected 1 arguments but got 2.\n[line 1:9]\n"
+    );
+}
+
+#[test]
+fn clock_is_defined() {
+    let (mut lox, output, _) = session();
+    lox.run_source("print clock() > 0;").unwrap();
+    assert_eq!(output.contents(), "true\n");
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::{errors, output};
+
+#[test]
+fn remainder_and_floor_division() {
+    assert_eq!(
+        output("print 7 % 3; print -7 % 3; print 7 ~/ 2; print -7 ~/ 2; print 7.5 ~/ 2;"),
+        "1\n-1\n3\n-4\n3\n"
+    );
+}
+
+#[test]
+fn exponentiation_is_right_associative_and_binds_tighter_than_minus() {
+    assert_eq!(
+        output("print 2 ** 10; print 2 ** -1; print 2 ** 3 ** 2; print -2 ** 2;"),
+        "1024\n0.5\n512\n-4\n"
+    );
+}
+
+#[test]
+fn bitwise_and_shift_operators() {
+    assert_eq!(
+        output("print 6 & 3; print 6 | 3; print 6 ^ 3; print ~5; print -16 >> 2;"),
+        "2\n7\n5\n-6\n-4\n"
+    );
+    assert_eq!(
+        output("print 1 << 62; print 1 << 64;"),
+        "4611686018427387904\n18446744073709551616\n"
+    );
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(
+        output("print 1 + 2 * 3 ** 2; print 1 | 2 & 3; print 1 << 2 + 1;"),
+        "19\n3\n8\n"
+    );
+}
+
+#[test]
+fn operator_errors() {
+    assert_eq!(
+        errors("print 1 << -1;"),
+        "Shift amount must not be negative.\n[line 1:9]\n"
+    );
+    assert_eq!(
+        errors("print 1.5 & 1;"),
+        "Operands of '&' must be integers.\n[line 1:11]\n"
+    );
+    assert_eq!(
+        errors("print 2 ** 100000000;"),
+        "Result of '**' is too large.\n[line 1:9]\n"
+    );
+    assert_eq!(
+        errors("print 5 % 0;"),
+        "Integer division by zero.\n[line 1:9]\n"
+    );
+}
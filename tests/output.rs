@@ -0,0 +1,37 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rust_lox::{
+    diagnostic::ErrorFormat,
+    output::{Buffer, Sink},
+    Lox,
+};
+
+#[test]
+fn print_and_errors_go_to_their_own_sinks() {
+    let output = Buffer::new();
+    let diagnostics = Buffer::new();
+    let mut lox = Lox::new();
+    lox.set_output(output.clone());
+    lox.set_diagnostics(diagnostics.clone());
+    lox.set_error_format(ErrorFormat::Short);
+
+    assert!(lox.run_source("print \"out\"; print 1 + nil;").is_err());
+    assert_eq!(output.contents(), "out\n");
+    assert_eq!(
+        diagnostics.contents(),
+        "Operands must be two numbers or two strings.\n[line 1:22]\n"
+    );
+}
+
+#[test]
+fn output_can_be_streamed_to_a_callback() {
+    let lines = Rc::new(RefCell::new(String::new()));
+    let received = Rc::clone(&lines);
+    let mut lox = Lox::new();
+    lox.set_output(Sink::from_fn(move |chunk| {
+        received.borrow_mut().push_str(chunk)
+    }));
+    lox.run_source("for (var i = 0; i < 3; i++) print i;")
+        .unwrap();
+    assert_eq!(*lines.borrow(), "0\n1\n2\n");
+}
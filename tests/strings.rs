@@ -0,0 +1,52 @@
+mod common;
+
+use common::{errors, output};
+
+#[test]
+fn interpolation_evaluates_expressions() {
+    assert_eq!(
+        output("var n = 2; print \"n=${n}, twice ${n * 2}!\";"),
+        "n=2, twice 4!\n"
+    );
+}
+
+#[test]
+fn interpolated_values_print_like_print() {
+    assert_eq!(
+        output("print \"${nil} ${true} ${1.5} ${[1, \"s\"]}\";"),
+        "nil true 1.5 [1, \"s\"]\n"
+    );
+}
+
+#[test]
+fn interpolations_nest_and_may_contain_braces() {
+    assert_eq!(output("print \"${\"in${1 + 1}ner\"}\";"), "in2ner\n");
+    assert_eq!(
+        output("var m = {\"a\": 1}; print \"${m[\"a\"]} ${ {\"b\": 2} }\";"),
+        "1 {\"b\": 2}\n"
+    );
+}
+
+#[test]
+fn triple_quoted_strings_interpolate() {
+    assert_eq!(output("print \"\"\"x ${1}\n\"y\" \"\"\";"), "x 1\n\"y\" \n");
+}
+
+#[test]
+fn escaped_dollar_is_not_interpolated() {
+    assert_eq!(output("print \"a\\${b}\";"), "a${b}\n");
+}
+
+#[test]
+fn interpolation_errors() {
+    assert_eq!(
+        errors("print \"${}\";"),
+        "[line 1:10] Error at '}\"': Expect expression.\n"
+    );
+    assert!(errors("print \"${1\";")
+        .starts_with("[line 1:8] Error: Unterminated string interpolation.\n"));
+    assert_eq!(
+        errors("print \"a ${nil + 1}\";"),
+        "Operands must be two numbers or two strings.\n[line 1:16]\n"
+    );
+}